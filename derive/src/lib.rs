@@ -0,0 +1,186 @@
+//! Derives for domcom, re-exported by its `derive` feature.
+//!
+//! ```ignore
+//! #[derive(DomcomTable, DomcomForm)]
+//! struct Reading {
+//!     #[domcom(label = "Max temp (°C)")]
+//!     max_temp: f32,
+//!     #[domcom(hidden)]
+//!     id: u64,
+//!     #[domcom(cell = station_name, input = station_picker)]
+//!     station: String,
+//! }
+//! ```
+//!
+//! Custom renderers are `cell = path`, a `fn(&T) -> String` for table cells, and
+//! `input = path`, with the signature of `FormInput::input`, for form inputs.
+//! Tables also accept `sort = path` to override a column's `SortFunction`.
+//! Forms read submitted values back with `FormInput::read`, or `read = path`, a
+//! `fn(&FieldValue) -> Option<T>`. A field with an `input` but no `read` is left
+//! alone by `FormModel::apply`, so its type needn't implement `FormInput`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, LitStr, Path, Type};
+
+/// One struct field and its `#[domcom(..)]` options
+struct Column {
+    ident: Ident,
+    ty: Type,
+    label: String,
+    hidden: bool,
+    cell: Option<Path>,
+    input: Option<Path>,
+    read: Option<Path>,
+    sort: Option<Path>,
+}
+
+/// `min_temp` becomes `Min temp`
+fn default_label(ident: &Ident) -> String {
+    let name = ident.to_string().trim_start_matches("r#").replace('_', " ");
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+fn columns(input: &DeriveInput) -> Result<Vec<Column>, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(&input.ident, "expected a struct with named fields")),
+        },
+        _ => return Err(Error::new_spanned(&input.ident, "expected a struct")),
+    };
+
+    fields
+        .iter()
+        .map(|field| {
+            let ident = field.ident.clone().expect("named field");
+            let mut column = Column {
+                label: default_label(&ident),
+                ident,
+                ty: field.ty.clone(),
+                hidden: false,
+                cell: None,
+                input: None,
+                read: None,
+                sort: None,
+            };
+
+            for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("domcom")) {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("label") {
+                        column.label = meta.value()?.parse::<LitStr>()?.value();
+                    } else if meta.path.is_ident("hidden") {
+                        column.hidden = true;
+                    } else if meta.path.is_ident("cell") {
+                        column.cell = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("input") {
+                        column.input = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("read") {
+                        column.read = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("sort") {
+                        column.sort = Some(meta.value()?.parse()?);
+                    } else {
+                        return Err(meta.error("expected `label`, `hidden`, `cell`, `input`, `read` or `sort`"));
+                    }
+                    Ok(())
+                })?;
+            }
+
+            Ok(column)
+        })
+        .collect()
+}
+
+fn expand(input: TokenStream, derive: fn(&DeriveInput, Vec<Column>) -> TokenStream2) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match columns(&input) {
+        Ok(columns) => derive(&input, columns.into_iter().filter(|column| !column.hidden).collect()).into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Implements `TableRow`, turning each visible field into a `Table` column
+#[proc_macro_derive(DomcomTable, attributes(domcom))]
+pub fn derive_table(input: TokenStream) -> TokenStream {
+    expand(input, |input, columns| {
+        let name = &input.ident;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        let columns = columns.iter().map(|Column { ident, ty, label, cell, sort, .. }| {
+            let sort = match (sort, cell) {
+                (Some(sort), _) => quote!(::std::option::Option::Some(#sort)),
+                (None, Some(_)) => quote!(::std::option::Option::Some(::domcom::components::table::alphabetical_sort)),
+                (None, None) => quote!(<#ty as ::domcom::components::table::TableCell>::sort()),
+            };
+            let cell = match cell {
+                Some(cell) => cell.to_token_stream(),
+                None => quote!(<#ty as ::domcom::components::table::TableCell>::cell),
+            };
+            quote! {
+                ::domcom::components::TableValues {
+                    title: #label.to_string(),
+                    values: rows.iter().map(|row| #cell(&row.#ident)).collect(),
+                    sort: #sort,
+                }
+            }
+        });
+
+        quote! {
+            impl #impl_generics ::domcom::components::table::TableRow for #name #ty_generics #where_clause {
+                fn columns(rows: &[Self]) -> ::std::vec::Vec<::domcom::components::TableValues> {
+                    ::std::vec![#(#columns),*]
+                }
+            }
+        }
+    })
+}
+
+/// Implements `FormModel`, registering a field and input for each visible field
+#[proc_macro_derive(DomcomForm, attributes(domcom))]
+pub fn derive_form(input: TokenStream) -> TokenStream {
+    expand(input, |input, columns| {
+        let name = &input.ident;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        let inputs = columns.iter().map(|Column { ident, ty, label, input, .. }| {
+            let key = ident.to_string();
+            let input = match input {
+                Some(input) => input.to_token_stream(),
+                None => quote!(<#ty as ::domcom::components::form::FormInput>::input),
+            };
+            quote!(#input(form, #key, #label, &self.#ident))
+        });
+
+        let reads = columns.iter().filter_map(|Column { ident, ty, input, read, .. }| {
+            let key = ident.to_string();
+            let read = match (read, input) {
+                (Some(read), _) => read.to_token_stream(),
+                (None, Some(_)) => return None,
+                (None, None) => quote!(<#ty as ::domcom::components::form::FormInput>::read),
+            };
+            Some(quote! {
+                if let ::std::option::Option::Some(value) = values.get(#key).and_then(#read) {
+                    self.#ident = value;
+                }
+            })
+        });
+
+        quote! {
+            impl #impl_generics ::domcom::components::form::FormModel for #name #ty_generics #where_clause {
+                fn inputs(&self, form: &::domcom::components::Form) -> ::std::vec::Vec<::domcom::reexports::dominator::Dom> {
+                    ::std::vec![#(#inputs),*]
+                }
+
+                #[allow(unused_variables)]
+                fn apply(&mut self, values: &::domcom::components::form::FormValues) {
+                    #(#reads)*
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,6 @@
+use domcom::DomcomForm;
+
+#[derive(DomcomForm)]
+struct Station(String);
+
+fn main() {}
@@ -0,0 +1,9 @@
+use domcom::DomcomTable;
+
+#[derive(DomcomTable)]
+struct Station {
+    #[domcom(colour = "red")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,69 @@
+// Only `domcom` is a dependency here, so the derive must name `Dom` through it
+use domcom::{
+    components::{
+        form::{FieldValue, FormModel, FormValues},
+        Form,
+    },
+    reexports::dominator::{html, Dom},
+    DomcomForm,
+};
+
+/// Doesn't implement `FormInput`, so it can only use a custom `input`
+#[derive(Debug, PartialEq)]
+struct Colour(String);
+
+fn colour_picker(form: &Form, name: &str, _label: &str, value: &Colour) -> Dom {
+    form.select(name, &value.0);
+    html!("div")
+}
+
+fn read_colour(value: &FieldValue) -> Option<Colour> {
+    match value {
+        FieldValue::Text(text) => Some(Colour(text.clone())),
+        _ => None,
+    }
+}
+
+#[derive(DomcomForm)]
+struct Settings {
+    name: String,
+    #[domcom(label = "Age (years)")]
+    age: u8,
+    subscribed: bool,
+    #[domcom(input = colour_picker, read = read_colour)]
+    accent: Colour,
+    #[domcom(input = colour_picker)]
+    background: Colour,
+    #[domcom(hidden)]
+    id: u64,
+}
+
+fn main() {
+    let mut settings = Settings {
+        name: "Ada".to_string(),
+        age: 36,
+        subscribed: false,
+        accent: Colour("blue".to_string()),
+        background: Colour("white".to_string()),
+        id: 1,
+    };
+
+    let values = FormValues::from([
+        ("name".to_string(), FieldValue::Text("Grace".to_string())),
+        ("age".to_string(), FieldValue::Number(Some(-1.0))),
+        ("subscribed".to_string(), FieldValue::Bool(true)),
+        ("accent".to_string(), FieldValue::Text("red".to_string())),
+        ("background".to_string(), FieldValue::Text("black".to_string())),
+        ("id".to_string(), FieldValue::Number(Some(2.0))),
+    ]);
+    settings.apply(&values);
+
+    assert_eq!(settings.name, "Grace");
+    // Out of range for `u8`, so left alone
+    assert_eq!(settings.age, 36);
+    assert!(settings.subscribed);
+    assert_eq!(settings.accent, Colour("red".to_string()));
+    // No `read`, so left alone
+    assert_eq!(settings.background, Colour("white".to_string()));
+    assert_eq!(settings.id, 1);
+}
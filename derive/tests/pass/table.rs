@@ -0,0 +1,37 @@
+use domcom::{components::table::TableRow, DomcomTable};
+
+fn yes_no(value: &bool) -> String {
+    if *value { "Yes" } else { "No" }.to_string()
+}
+
+fn never(_: &str, _: &str) -> domcom::components::table::SortMovement {
+    domcom::components::table::SortMovement::None
+}
+
+#[derive(DomcomTable)]
+struct Station {
+    name: String,
+    #[domcom(label = "Elevation (m)")]
+    elevation: u32,
+    #[domcom(cell = yes_no)]
+    staffed: bool,
+    #[domcom(sort = never)]
+    code: Option<char>,
+    #[domcom(hidden)]
+    #[allow(dead_code)]
+    id: u64,
+}
+
+fn main() {
+    let columns = Station::columns(&[
+        Station { name: "Thredbo".to_string(), elevation: 1380, staffed: true, code: Some('T'), id: 1 },
+        Station { name: "Perisher".to_string(), elevation: 1720, staffed: false, code: None, id: 2 },
+    ]);
+
+    let titles = columns.iter().map(|column| column.title.as_str()).collect::<Vec<_>>();
+    assert_eq!(titles, ["Name", "Elevation (m)", "Staffed", "Code"]);
+    assert_eq!(columns[1].values, ["1380", "1720"]);
+    assert_eq!(columns[2].values, ["Yes", "No"]);
+    assert_eq!(columns[3].values, ["T", ""]);
+    assert!(columns.iter().all(|column| column.sort.is_some()));
+}
@@ -1,11 +1,19 @@
 use std::rc::Rc;
 
-use dominator::{class, html, pseudo, with_node, Dom};
+use dominator::{apply_methods, class, pseudo, with_node, Dom, DomBuilder};
 use factoryizer::Factory;
+use futures_signals::{
+    map_ref,
+    signal::{Broadcaster, SignalExt},
+};
+use web_sys::HtmlElement;
 
-use crate::helpers::colours::{bw_on_bg, darken, opacity, TRANSPARENT};
+use crate::helpers::{
+    colours::{best_text_on, css_opacity, Rgba, BLACK, TRANSPARENT, WHITE},
+    theme::check_contrast,
+};
 
-use super::ty::{Colour, Component, Reactive, RemSizing};
+use super::ty::{Colour, Component, Length, Props, Reactive};
 
 #[derive(Default, Clone)]
 pub enum ButtonVariant {
@@ -16,26 +24,80 @@ pub enum ButtonVariant {
     Subtle,
 }
 
+lazy_static::lazy_static! {
+    static ref BUTTON_CLASS: String = class! {
+        .style("color", "var(--domcom-button-colour)")
+        .style("background", "var(--domcom-button-background)")
+        .style("outline", "var(--domcom-button-outline)")
+        .pseudo!(":hover", {
+            .style("background", "var(--domcom-button-hover)")
+        })
+        .pseudo!(":active", {
+            .style("transform", "translateY(0.15rem)")
+            .style("background", "var(--domcom-button-active)")
+        })
+    };
+}
+
 #[derive(Factory, Default)]
 #[into]
 pub struct Button {
     value: Reactive<String>,
-    variant: ButtonVariant,
-    colour: Colour,
-    size: RemSizing,
-    radius: RemSizing,
-    padding: RemSizing,
+    variant: Reactive<ButtonVariant>,
+    colour: Reactive<Colour>,
+    size: Reactive<Length>,
+    radius: Reactive<Length>,
+    padding: Reactive<Length>,
 
     #[skip]
     on_click: Option<Rc<dyn Fn()>>,
     #[skip]
-    styles: Vec<(String, Reactive<String>)>,
-    #[skip]
-    classes: Vec<String>,
+    props: Props,
     #[skip]
     children: Vec<Dom>,
 }
 
+/// CSS values for one colour/variant pair, applied as custom properties
+/// so the `:hover` and `:active` rules can follow prop changes
+#[derive(Clone)]
+struct ButtonStyle {
+    colour: String,
+    background: String,
+    outline: String,
+    hover: String,
+    active: String,
+}
+
+impl ButtonStyle {
+    fn new(colour: &Colour, variant: &ButtonVariant) -> Self {
+        ButtonStyle {
+            colour: match variant {
+                ButtonVariant::Filled => colour.contrast_var(),
+                _ => colour.var(),
+            },
+            background: match variant {
+                ButtonVariant::Filled => colour.var(),
+                ButtonVariant::Light => css_opacity(&colour.var(), 0.15),
+                ButtonVariant::Subtle | ButtonVariant::Outline => TRANSPARENT.to_string(),
+            },
+            outline: match variant {
+                ButtonVariant::Outline => format!("2px solid {}", colour.var()),
+                _ => TRANSPARENT.to_string(),
+            },
+            hover: match variant {
+                ButtonVariant::Filled => colour.shade(600),
+                ButtonVariant::Light => colour.shade(100),
+                ButtonVariant::Subtle | ButtonVariant::Outline => colour.shade(50),
+            },
+            active: match variant {
+                ButtonVariant::Filled => colour.shade(700),
+                ButtonVariant::Light => colour.shade(200),
+                ButtonVariant::Subtle | ButtonVariant::Outline => colour.shade(100),
+            },
+        }
+    }
+}
+
 impl Button {
     pub fn text(&mut self, text: &str) -> &mut Self {
         self.value = Reactive::Static(text.to_string());
@@ -52,65 +114,51 @@ impl Button {
 }
 
 impl Component for Button {
-    fn style(&mut self, style: (String, Reactive<String>)) -> &mut Self {
-        self.styles.push(style);
-        self
+    fn props(&mut self) -> &mut Props {
+        &mut self.props
     }
 
-    fn dom(&mut self) -> dominator::Dom {
-        html!("button", {
-            .with_node!(_e => {
-                .event({
-                    let on_click = self.on_click.clone().unwrap_or(Rc::new(|| {}));
-                    move |_evt: dominator::events::Click| {
-                        on_click();
-                    }
-                })
-                .class("font-semibold")
-                .class(
-                    class! {
-                        .style("padding", &format!("{} {}", self.padding.mult(0.5).to_string(), self.padding.to_string()))
-                        .style("border-radius", &self.radius.mult(0.45).to_string())
-                        .style("font-size", &self.size.to_string())
-                        .style("color", match self.variant {
-                            ButtonVariant::Filled => bw_on_bg(self.colour.to_string()),
-                            _ => self.colour.to_string(),
-                        })
-                        .style("outline", &match self.variant {
-                            ButtonVariant::Outline => format!("2px solid {}", self.colour.to_string()),
-                            _ => TRANSPARENT.to_string(),
-                        })
-                        .style("background", &match self.variant {
-                            ButtonVariant::Filled => self.colour.to_string(),
-                            ButtonVariant::Light => opacity(self.colour.to_string(), 0.15),
-                            ButtonVariant::Subtle | ButtonVariant::Outline => {
-                                TRANSPARENT.to_string()
-                            }
-                        })
-                        .pseudo!(":hover", {
-                            .style("background", &match self.variant {
-                                ButtonVariant::Filled => darken(self.colour.to_string(), 0.25),
-                                ButtonVariant::Light => opacity(self.colour.to_string(), 0.25),
-                                ButtonVariant::Subtle | ButtonVariant::Outline => opacity(self.colour.to_string(), 0.15),
-                            })
-                        })
-                        .pseudo!(":active", {
-                            .style("transform", "translateY(0.15rem)")
+    fn render(&mut self) -> DomBuilder<HtmlElement> {
+        let style = Broadcaster::new(map_ref! {
+            let colour = self.colour.signal(),
+            let variant = self.variant.signal() => ButtonStyle::new(colour, variant)
+        });
+
+        apply_methods!(DomBuilder::<HtmlElement>::new_html("button"), {
+            .with_node!(e => {
+                .future(map_ref! {
+                    let colour = self.colour.signal(),
+                    let variant = self.variant.signal() => (colour.clone(), variant.clone())
+                }.for_each(move |(colour, variant)| {
+                    check_contrast(&e, "Button", |p, _| {
+                        let c = Rgba::parse(&colour.resolve(p)).ok()?;
+                        Some(match variant {
+                            ButtonVariant::Filled => (best_text_on(c, WHITE, &[BLACK, WHITE]), c),
+                            ButtonVariant::Light => (c, c.with_alpha(0.15)),
+                            ButtonVariant::Subtle | ButtonVariant::Outline => (c, BLACK.with_alpha(0.0)),
                         })
-                    }
-                )
+                    });
+                    async {}
+                }))
             })
-            .children(self.children.iter_mut().map(|c| c))
-            .apply(|mut d| {
-                d = self.value.apply_text(d);
-                for (k, v) in self.styles.iter() {
-                    d = v.apply_style(k.to_string(), d);
-                }
-                for c in self.classes.iter() {
-                    d = d.class(c);
+            .event({
+                let on_click = self.on_click.clone().unwrap_or(Rc::new(|| {}));
+                move |_evt: dominator::events::Click| {
+                    on_click();
                 }
-                d
             })
+            .class("font-semibold")
+            .style_signal("--domcom-button-colour", style.signal_ref(|s| s.colour.clone()))
+            .style_signal("--domcom-button-background", style.signal_ref(|s| s.background.clone()))
+            .style_signal("--domcom-button-outline", style.signal_ref(|s| s.outline.clone()))
+            .style_signal("--domcom-button-hover", style.signal_ref(|s| s.hover.clone()))
+            .style_signal("--domcom-button-active", style.signal_ref(|s| s.active.clone()))
+            .style_signal("padding", self.padding.signal().map(|p| format!("{} {}", p.mult(0.5).var(), p.var())))
+            .style_signal("border-radius", self.radius.signal().map(|r| r.mult(0.45).var()))
+            .style_signal("font-size", self.size.signal().map(|s| s.var()))
+            .class(&*BUTTON_CLASS)
+            .children(self.children.iter_mut())
+            .apply(|d| self.value.apply_text(d))
         })
     }
 }
@@ -0,0 +1,134 @@
+use dominator::{apply_methods, class, events, html, pseudo, with_node, Dom, DomBuilder};
+use factoryizer::Factory;
+use futures_signals::signal::{Mutable, SignalExt};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::helpers::safety::rand_id;
+
+use super::{
+    form::Field,
+    ty::{Colour, Component, Length, Props, Reactive},
+};
+
+lazy_static::lazy_static! {
+    /// Native checkbox and radio inputs, sized by the font and tinted with `--domcom-toggle-colour`
+    pub(super) static ref TOGGLE_CLASS: String = class! {
+        .style("width", "1em")
+        .style("height", "1em")
+        .style("margin", "0")
+        .style("flex-shrink", "0")
+        .style("accent-color", "var(--domcom-toggle-colour)")
+        .style("cursor", "pointer")
+        .pseudo!(":disabled", {
+            .style("cursor", "not-allowed")
+        })
+    };
+}
+
+/// Label, description and error around a checkbox-like `control`,
+/// which should point `aria-describedby` at `{id}-description {id}-error`
+pub(super) fn labelled(
+    id: &str,
+    control: Dom,
+    label: &Reactive<String>,
+    description: &Reactive<String>,
+    error: &Reactive<String>,
+    size: &Reactive<Length>,
+    disabled: &Reactive<bool>,
+) -> DomBuilder<HtmlElement> {
+    apply_methods!(DomBuilder::<HtmlElement>::new_html("div"), {
+        .class("flex")
+        .class("flex-col")
+        .class("gap-1")
+        .style_signal("font-size", size.signal().map(|s| s.var()))
+        .class_signal("opacity-60", disabled.signal())
+        .child(html!("label", {
+            .class("flex")
+            .class("items-center")
+            .class("gap-2")
+            .class_signal("cursor-not-allowed", disabled.signal())
+            .child(control)
+            .child(html!("span", {
+                .text_signal(label.signal())
+            }))
+        }))
+        .child(html!("p", {
+            .class("text-[0.85em]")
+            .class("opacity-75")
+            .attr("id", &format!("{}-description", id))
+            .visible_signal(description.signal().map(|description| !description.is_empty()))
+            .text_signal(description.signal())
+        }))
+        .child(html!("p", {
+            .class("text-[0.85em]")
+            .attr("id", &format!("{}-error", id))
+            .attr("aria-live", "polite")
+            .style("color", &Colour::Danger.var())
+            .visible_signal(error.signal().map(|error| !error.is_empty()))
+            .text_signal(error.signal())
+        }))
+    })
+}
+
+#[derive(Factory, Default)]
+#[into]
+pub struct Checkbox {
+    pub checked: Mutable<bool>,
+    /// Shows a dash instead of a tick, cleared when the box is toggled
+    pub indeterminate: Mutable<bool>,
+    label: Reactive<String>,
+    description: Reactive<String>,
+    error: Reactive<String>,
+    colour: Reactive<Colour>,
+    size: Reactive<Length>,
+    disabled: Reactive<bool>,
+
+    #[skip]
+    touched: Option<Mutable<bool>>,
+    #[skip]
+    props: Props,
+}
+
+impl Checkbox {
+    /// Binds the checked state and error to a form field, touching it on change
+    pub fn field(&mut self, field: &Field<bool>) -> &mut Self {
+        field.bind(&mut self.checked, &mut self.error, &mut self.touched);
+        self
+    }
+}
+
+impl Component for Checkbox {
+    fn props(&mut self) -> &mut Props {
+        &mut self.props
+    }
+
+    fn render(&mut self) -> DomBuilder<HtmlElement> {
+        let id = rand_id();
+        let control = html!("input" => HtmlInputElement, {
+            .class(&*TOGGLE_CLASS)
+            .attr("type", "checkbox")
+            .attr("aria-describedby", &format!("{0}-description {0}-error", id))
+            .attr_signal("aria-invalid", self.error.signal().map(|error| (!error.is_empty()).then_some("true")))
+            .style_signal("--domcom-toggle-colour", self.colour.signal().map(|c| c.var()))
+            .prop_signal("checked", self.checked.signal())
+            .prop_signal("indeterminate", self.indeterminate.signal())
+            .prop_signal("disabled", self.disabled.signal())
+            .with_node!(input => {
+                .event({
+                    let checked = self.checked.clone();
+                    let indeterminate = self.indeterminate.clone();
+                    let touched = self.touched.take();
+                    move |_: events::Change| {
+                        checked.set_neq(input.checked());
+                        indeterminate.set_neq(false);
+                        if let Some(touched) = &touched {
+                            touched.set_neq(true);
+                        }
+                    }
+                })
+            })
+        });
+
+        labelled(&id, control, &self.label, &self.description, &self.error, &self.size, &self.disabled)
+    }
+}
@@ -1,7 +1,10 @@
-use dominator::{class, html, pseudo, Dom};
+use std::fmt::{self, Display};
+
+use dominator::{apply_methods, class, Dom, DomBuilder};
 use factoryizer::Factory;
+use web_sys::HtmlElement;
 
-use super::ty::{Component, Reactive, RemSizing};
+use super::ty::{Component, Length, Props, Responsive};
 
 #[derive(Default, Clone)]
 pub enum FlexDirection {
@@ -12,30 +15,30 @@ pub enum FlexDirection {
     ColumnReverse,
 }
 
-impl ToString for FlexDirection {
-    fn to_string(&self) -> String {
-        match self {
+impl Display for FlexDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
             FlexDirection::Row => "row",
             FlexDirection::Column => "column",
             FlexDirection::RowReverse => "row-reverse",
             FlexDirection::ColumnReverse => "column-reverse",
-        }
-        .to_string()
+        })
     }
 }
 
 #[derive(Factory, Default)]
+#[into]
 pub struct Flex {
     as_tag: Option<&'static str>,
-    space_x: RemSizing,
-    space_y: RemSizing,
+    space_x: Responsive<Length>,
+    space_y: Responsive<Length>,
     wrap: bool,
-    direction: FlexDirection,
+    direction: Responsive<FlexDirection>,
 
     #[skip]
     children: Vec<Dom>,
     #[skip]
-    styles: Vec<(String, Reactive<String>)>,
+    props: Props,
 }
 
 impl Flex {
@@ -46,16 +49,14 @@ impl Flex {
 }
 
 impl Component for Flex {
-    fn style(&mut self, style: (String, Reactive<String>)) -> &mut Self {
-        self.styles.push(style);
-        self
+    fn props(&mut self) -> &mut Props {
+        &mut self.props
     }
-    fn dom(&mut self) -> dominator::Dom {
-        html!(self.as_tag.unwrap_or("div"), {
+    fn render(&mut self) -> DomBuilder<HtmlElement> {
+        apply_methods!(DomBuilder::<HtmlElement>::new_html(self.as_tag.unwrap_or("div")), {
             .class(
                 class! {
                     .style("display", "flex")
-                    .style("flex-direction", &self.direction.to_string())
                     .style(
                         "flex-wrap",
                         &match self.wrap {
@@ -63,13 +64,12 @@ impl Component for Flex {
                             false => "nowrap",
                         }
                     )
-                    .pseudo!("> * + *", {
-                        .style("margin-left", &self.space_x.to_string())
-                        .style("margin-top", &self.space_y.to_string())
-                    })
                 }
             )
-            .children(self.children.iter_mut().map(|c| c))
+            .class(&self.direction.class("", |d| vec![("flex-direction", d.to_string())]))
+            .class(&self.space_x.class(" > * + *", |s| vec![("margin-left", s.var())]))
+            .class(&self.space_y.class(" > * + *", |s| vec![("margin-top", s.var())]))
+            .children(self.children.iter_mut())
         })
     }
 }
@@ -0,0 +1,611 @@
+//! Form state: fields registered by name, validation and submission.
+//!
+//! ```ignore
+//! let form = Form::new();
+//! let email = form.text("email", "").required("Enter your email");
+//! form.on_submit(|values| async move { save(values).await });
+//! form.dom(vec![TextInput::new().label("Email").field(&email).dom(), submit_button])
+//! ```
+//!
+//! Errors show once a field has been touched (blurred or changed) or a submit
+//! was attempted, so untouched forms don't open covered in red.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    rc::{Rc, Weak},
+};
+
+use dominator::{events, html, Dom};
+use futures_signals::{
+    map_ref,
+    signal::{LocalBoxSignal, Mutable, Signal, SignalExt},
+    signal_vec::{MutableVec, SignalVecExt},
+};
+
+use super::{ty::Reactive, Checkbox, Component, TextInput};
+
+type LocalFuture<T> = Pin<Box<dyn Future<Output = T>>>;
+type SyncValidator<T> = Rc<dyn Fn(&T) -> Result<(), String>>;
+type AsyncValidator<T> = Rc<dyn Fn(T) -> LocalFuture<Result<(), String>>>;
+type SubmitHandler = Rc<dyn Fn(FormValues) -> LocalFuture<()>>;
+
+pub type FormValues = HashMap<String, FieldValue>;
+
+/// A field's value as handed to the submit handler
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(untagged))]
+pub enum FieldValue {
+    Text(String),
+    /// `None` while the input is empty
+    Number(Option<f64>),
+    Bool(bool),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldKind {
+    Text,
+    Number,
+    Checkbox,
+    Select,
+}
+
+/// Types a [`Field`] can hold
+pub trait FieldType: Clone + PartialEq + 'static {
+    fn is_empty(&self) -> bool;
+    fn to_value(&self, kind: FieldKind) -> FieldValue;
+}
+
+impl FieldType for String {
+    fn is_empty(&self) -> bool {
+        self.trim().is_empty()
+    }
+
+    fn to_value(&self, kind: FieldKind) -> FieldValue {
+        match kind {
+            FieldKind::Number => FieldValue::Number(self.trim().parse().ok().filter(|n: &f64| n.is_finite())),
+            _ => FieldValue::Text(self.clone()),
+        }
+    }
+}
+
+impl FieldType for bool {
+    fn is_empty(&self) -> bool {
+        !self
+    }
+
+    fn to_value(&self, _: FieldKind) -> FieldValue {
+        FieldValue::Bool(*self)
+    }
+}
+
+enum Validator<T> {
+    Sync(SyncValidator<T>),
+    Async(AsyncValidator<T>),
+}
+
+struct FieldState<T> {
+    name: String,
+    kind: FieldKind,
+    initial: T,
+    value: Mutable<T>,
+    error: Mutable<Option<String>>,
+    touched: Mutable<bool>,
+    validating: Mutable<bool>,
+    submitted: Mutable<bool>,
+    validators: RefCell<Vec<Validator<T>>>,
+    /// Bumped on every validation so slow async results for old values are dropped
+    generation: Cell<u32>,
+    /// The generation of the last validation to finish
+    settled: Mutable<u32>,
+}
+
+/// A registered field, cheap to clone. Bind it to an input with e.g. [`TextInput::field`](super::TextInput::field).
+#[derive(Clone)]
+pub struct Field<T> {
+    state: Rc<FieldState<T>>,
+}
+
+impl<T: FieldType> Field<T> {
+    fn new(name: &str, kind: FieldKind, initial: T, submitted: Mutable<bool>) -> Self {
+        let field = Self {
+            state: Rc::new(FieldState {
+                name: name.to_string(),
+                kind,
+                initial: initial.clone(),
+                value: Mutable::new(initial),
+                error: Mutable::new(None),
+                touched: Mutable::new(false),
+                validating: Mutable::new(false),
+                submitted,
+                validators: RefCell::new(Vec::new()),
+                generation: Cell::new(0),
+                settled: Mutable::new(0),
+            }),
+        };
+
+        let state = Rc::downgrade(&field.state);
+        wasm_bindgen_futures::spawn_local(field.state.value.signal_cloned().for_each(move |_| {
+            let field = Weak::upgrade(&state).map(|state| Field { state });
+            async move {
+                if let Some(field) = field {
+                    field.validate().await;
+                }
+            }
+        }));
+
+        field
+    }
+
+    pub fn name(&self) -> &str {
+        &self.state.name
+    }
+
+    pub fn kind(&self) -> FieldKind {
+        self.state.kind
+    }
+
+    pub fn mutable(&self) -> Mutable<T> {
+        self.state.value.clone()
+    }
+
+    pub fn get(&self) -> T {
+        self.state.value.get_cloned()
+    }
+
+    pub fn set(&self, value: T) {
+        self.state.value.set_neq(value);
+        self.touch();
+    }
+
+    /// Adds a check run on every change, returning the error message on failure
+    pub fn validate_with(self, validator: impl Fn(&T) -> Result<(), String> + 'static) -> Self {
+        self.state.validators.borrow_mut().push(Validator::Sync(Rc::new(validator)));
+        self
+    }
+
+    /// Adds a check that needs to wait, e.g. on the server. It runs after the sync ones pass.
+    pub fn validate_async<F>(self, validator: impl Fn(T) -> F + 'static) -> Self
+    where
+        F: Future<Output = Result<(), String>> + 'static,
+    {
+        self.state
+            .validators
+            .borrow_mut()
+            .push(Validator::Async(Rc::new(move |value| Box::pin(validator(value)))));
+        self
+    }
+
+    pub fn required(self, message: &str) -> Self {
+        let message = message.to_string();
+        self.validate_with(move |value| if value.is_empty() { Err(message.clone()) } else { Ok(()) })
+    }
+
+    /// Runs the validators against the current value, updating the error.
+    /// If the value changes meanwhile, resolves to the result for the newer value.
+    pub fn validate(&self) -> impl Future<Output = bool> {
+        let state = self.state.clone();
+        let generation = state.generation.get().wrapping_add(1);
+        state.generation.set(generation);
+        let value = state.value.get_cloned();
+
+        let validators = state
+            .validators
+            .borrow()
+            .iter()
+            .map(|validator| match validator {
+                Validator::Sync(validator) => Validator::Sync(validator.clone()),
+                Validator::Async(validator) => Validator::Async(validator.clone()),
+            })
+            .collect::<Vec<_>>();
+
+        async move {
+            let mut result = Ok(());
+            for validator in validators.iter() {
+                if let Validator::Sync(validator) = validator {
+                    result = result.and_then(|_| validator(&value));
+                }
+            }
+
+            if result.is_ok() {
+                state.validating.set_neq(true);
+                for validator in validators.iter() {
+                    if let Validator::Async(validator) = validator {
+                        result = validator(value.clone()).await;
+                        if result.is_err() || state.generation.get() != generation {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            if state.generation.get() != generation {
+                return Self::latest_result(&state).await;
+            }
+            state.validating.set_neq(false);
+            let valid = result.is_ok();
+            state.error.set_neq(result.err());
+            state.settled.set(generation);
+            valid
+        }
+    }
+
+    /// Waits for the newest validation to finish, resolving to whether it passed
+    async fn latest_result(state: &FieldState<T>) -> bool {
+        loop {
+            let generation = state.generation.get();
+            state.settled.signal().wait_for(generation).await;
+            if state.generation.get() == generation {
+                return state.error.lock_ref().is_none();
+            }
+        }
+    }
+
+    /// Marks the field as visited, showing its error
+    pub fn touch(&self) {
+        self.state.touched.set_neq(true);
+    }
+
+    pub fn touched(&self) -> Mutable<bool> {
+        self.state.touched.clone()
+    }
+
+    /// The error to show, `None` until the field is touched or a submit is attempted
+    pub fn error_signal(&self) -> impl Signal<Item = Option<String>> {
+        map_ref! {
+            let error = self.state.error.signal_cloned(),
+            let touched = self.state.touched.signal(),
+            let submitted = self.state.submitted.signal() => {
+                if *touched || *submitted { error.clone() } else { None }
+            }
+        }
+    }
+
+    pub fn valid_signal(&self) -> impl Signal<Item = bool> {
+        map_ref! {
+            let error = self.state.error.signal_ref(Option::is_none),
+            let validating = self.state.validating.signal() => *error && !*validating
+        }
+    }
+
+    pub fn validating_signal(&self) -> impl Signal<Item = bool> {
+        self.state.validating.signal()
+    }
+
+    /// Whether the value differs from the one the field was registered with
+    pub fn dirty_signal(&self) -> impl Signal<Item = bool> {
+        let state = self.state.clone();
+        self.state.value.signal_ref(move |value| *value != state.initial)
+    }
+
+    pub fn reset(&self) {
+        self.state.value.set_neq(self.state.initial.clone());
+        self.state.touched.set_neq(false);
+    }
+
+    /// Points a component's value, error and touched props at this field,
+    /// shared by the components' `field` methods
+    pub(super) fn bind(&self, value: &mut Mutable<T>, error: &mut Reactive<String>, touched: &mut Option<Mutable<bool>>) {
+        *value = self.mutable();
+        *error = Reactive::from_signal(self.error_signal().map(Option::unwrap_or_default));
+        *touched = Some(self.touched());
+    }
+}
+
+/// The parts of a field the form needs, whatever its value type
+trait AnyField {
+    fn name(&self) -> &str;
+    fn value(&self) -> FieldValue;
+    fn validate(&self) -> LocalFuture<bool>;
+    fn touch(&self);
+    fn reset(&self);
+    fn valid_signal(&self) -> LocalBoxSignal<'static, bool>;
+    fn dirty_signal(&self) -> LocalBoxSignal<'static, bool>;
+}
+
+impl<T: FieldType> AnyField for Field<T> {
+    fn name(&self) -> &str {
+        Field::name(self)
+    }
+
+    fn value(&self) -> FieldValue {
+        self.state.value.lock_ref().to_value(self.state.kind)
+    }
+
+    fn validate(&self) -> LocalFuture<bool> {
+        Box::pin(Field::validate(self))
+    }
+
+    fn touch(&self) {
+        Field::touch(self)
+    }
+
+    fn reset(&self) {
+        Field::reset(self)
+    }
+
+    fn valid_signal(&self) -> LocalBoxSignal<'static, bool> {
+        Field::valid_signal(self).boxed_local()
+    }
+
+    fn dirty_signal(&self) -> LocalBoxSignal<'static, bool> {
+        Field::dirty_signal(self).boxed_local()
+    }
+}
+
+/// A value that can be edited in a form, see `#[derive(DomcomForm)]`
+pub trait FormInput: Sized {
+    /// Registers a field called `name` starting at `value` and returns its input
+    fn input(form: &Form, name: &str, label: &str, value: &Self) -> Dom;
+    fn read(value: &FieldValue) -> Option<Self>;
+}
+
+impl FormInput for String {
+    fn input(form: &Form, name: &str, label: &str, value: &Self) -> Dom {
+        TextInput::new().label(label.to_string()).field(&form.text(name, value)).dom()
+    }
+
+    fn read(value: &FieldValue) -> Option<Self> {
+        match value {
+            FieldValue::Text(text) => Some(text.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// `number` if it is whole and within `min..=max`, integer bounds cast to `f64`
+fn whole_number(number: f64, min: f64, max: f64) -> Option<f64> {
+    // Wide types' `max` rounds up to a power of two, where `+ 1.0` is absorbed and the bound stays exact
+    (number.fract() == 0.0 && number >= min && number < max + 1.0).then_some(number)
+}
+
+macro_rules! integer_inputs {
+    ($($ty:ty),*) => {
+        $(impl FormInput for $ty {
+            fn input(form: &Form, name: &str, label: &str, value: &Self) -> Dom {
+                let field = form.number(name, Some(*value as f64)).validate_with(|value| {
+                    match value.trim().parse::<f64>() {
+                        Ok(number) if whole_number(number, <$ty>::MIN as f64, <$ty>::MAX as f64).is_none() => {
+                            Err(format!("Enter a whole number from {} to {}", <$ty>::MIN, <$ty>::MAX))
+                        }
+                        _ => Ok(()),
+                    }
+                });
+                TextInput::new().label(label.to_string()).field(&field).dom()
+            }
+
+            fn read(value: &FieldValue) -> Option<Self> {
+                match value {
+                    FieldValue::Number(Some(number)) => {
+                        whole_number(*number, <$ty>::MIN as f64, <$ty>::MAX as f64).map(|number| number as $ty)
+                    }
+                    _ => None,
+                }
+            }
+        })*
+    };
+}
+
+integer_inputs!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+macro_rules! float_inputs {
+    ($($ty:ty),*) => {
+        $(impl FormInput for $ty {
+            fn input(form: &Form, name: &str, label: &str, value: &Self) -> Dom {
+                TextInput::new().label(label.to_string()).field(&form.number(name, Some(*value as f64))).dom()
+            }
+
+            fn read(value: &FieldValue) -> Option<Self> {
+                match value {
+                    FieldValue::Number(Some(number)) => Some(*number as $ty).filter(|number| number.is_finite()),
+                    _ => None,
+                }
+            }
+        })*
+    };
+}
+
+float_inputs!(f32, f64);
+
+impl FormInput for bool {
+    fn input(form: &Form, name: &str, label: &str, value: &Self) -> Dom {
+        Checkbox::new().label(label.to_string()).field(&form.checkbox(name, *value)).dom()
+    }
+
+    fn read(value: &FieldValue) -> Option<Self> {
+        match value {
+            FieldValue::Bool(checked) => Some(*checked),
+            _ => None,
+        }
+    }
+}
+
+/// A struct edited as a whole form, usually derived with `#[derive(DomcomForm)]`
+pub trait FormModel {
+    /// Registers a field per struct field, starting from the current values, and returns their inputs
+    fn inputs(&self, form: &Form) -> Vec<Dom>;
+    /// Copies the submitted values back, leaving fields that weren't in the form alone
+    fn apply(&mut self, values: &FormValues);
+}
+
+/// A set of named fields, cheap to clone
+#[derive(Clone, Default)]
+pub struct Form {
+    fields: MutableVec<Rc<dyn AnyField>>,
+    submitted: Mutable<bool>,
+    submitting: Mutable<bool>,
+    on_submit: Rc<RefCell<Option<SubmitHandler>>>,
+}
+
+impl Form {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn register<T: FieldType>(&self, name: &str, kind: FieldKind, initial: T) -> Field<T> {
+        let field = Field::new(name, kind, initial, self.submitted.clone());
+        self.fields.lock_mut().push_cloned(Rc::new(field.clone()));
+        field
+    }
+
+    pub fn text(&self, name: &str, initial: &str) -> Field<String> {
+        self.register(name, FieldKind::Text, initial.to_string())
+    }
+
+    /// A text field submitted as a number, which rejects anything else
+    pub fn number(&self, name: &str, initial: Option<f64>) -> Field<String> {
+        let initial = initial.map(|n| n.to_string()).unwrap_or_default();
+        self.register(name, FieldKind::Number, initial).validate_with(|value| {
+            match value.trim().is_empty() || value.trim().parse::<f64>().is_ok_and(f64::is_finite) {
+                true => Ok(()),
+                false => Err("Enter a number".to_string()),
+            }
+        })
+    }
+
+    pub fn checkbox(&self, name: &str, initial: bool) -> Field<bool> {
+        self.register(name, FieldKind::Checkbox, initial)
+    }
+
+    /// A choice between fixed values, e.g. for a [`SegmentedControl`](super::SegmentedControl)
+    pub fn select(&self, name: &str, initial: &str) -> Field<String> {
+        self.register(name, FieldKind::Select, initial.to_string())
+    }
+
+    /// Registers the fields of `model`, returning their inputs. Read it back with [`FormModel::apply`].
+    pub fn model(&self, model: &impl FormModel) -> Vec<Dom> {
+        model.inputs(self)
+    }
+
+    pub fn values(&self) -> FormValues {
+        self.fields
+            .lock_ref()
+            .iter()
+            .map(|field| (field.name().to_string(), field.value()))
+            .collect()
+    }
+
+    pub fn value(&self, name: &str) -> Option<FieldValue> {
+        self.fields
+            .lock_ref()
+            .iter()
+            .find(|field| field.name() == name)
+            .map(|field| field.value())
+    }
+
+    pub fn valid_signal(&self) -> impl Signal<Item = bool> {
+        self.fields
+            .signal_vec_cloned()
+            .map_signal(|field| field.valid_signal())
+            .to_signal_map(|valid| valid.iter().all(|valid| *valid))
+    }
+
+    pub fn dirty_signal(&self) -> impl Signal<Item = bool> {
+        self.fields
+            .signal_vec_cloned()
+            .map_signal(|field| field.dirty_signal())
+            .to_signal_map(|dirty| dirty.iter().any(|dirty| *dirty))
+    }
+
+    /// Whether a submit is validating or running the handler
+    pub fn submitting_signal(&self) -> impl Signal<Item = bool> {
+        self.submitting.signal()
+    }
+
+    /// Called with the values when a submit passes validation
+    pub fn on_submit<F>(&self, handler: impl Fn(FormValues) -> F + 'static)
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        *self.on_submit.borrow_mut() = Some(Rc::new(move |values| Box::pin(handler(values))));
+    }
+
+    /// Validates every field and, if they all pass, runs the submit handler.
+    /// Resolves to whether it passed; a submit already in progress turns this one away with `false`.
+    pub fn submit(&self) -> impl Future<Output = bool> {
+        let form = self.clone();
+        async move {
+            // Claimed before validating, so a second submit during slow async checks is turned away
+            if form.submitting.replace(true) {
+                return false;
+            }
+            form.submitted.set_neq(true);
+
+            let fields = form.fields.lock_ref().to_vec();
+            let mut valid = true;
+            for field in fields.iter() {
+                field.touch();
+                valid &= field.validate().await;
+            }
+
+            let handler = form.on_submit.borrow().clone();
+            if let (true, Some(handler)) = (valid, handler) {
+                handler(form.values()).await;
+            }
+            form.submitting.set(false);
+            valid
+        }
+    }
+
+    /// Restores every field to its initial value and hides errors again
+    pub fn reset(&self) {
+        self.submitted.set_neq(false);
+        for field in self.fields.lock_ref().iter() {
+            field.reset();
+        }
+    }
+
+    /// A `<form>` around `children` that submits on enter or a submit button
+    pub fn dom(&self, children: impl IntoIterator<Item = Dom>) -> Dom {
+        html!("form", {
+            .attr("novalidate", "")
+            .attr_signal("aria-busy", self.submitting.signal().map(|busy| busy.then_some("true")))
+            .event_with_options(&dominator::EventOptions::preventable(), {
+                let form = self.clone();
+                move |e: events::Submit| {
+                    e.prevent_default();
+                    wasm_bindgen_futures::spawn_local({
+                        let submit = form.submit();
+                        async move {
+                            submit.await;
+                        }
+                    });
+                }
+            })
+            .children(children)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FieldValue, FormInput};
+
+    fn number(number: f64) -> FieldValue {
+        FieldValue::Number(Some(number))
+    }
+
+    #[test]
+    fn integers_reject_fractions_and_out_of_range() {
+        assert_eq!(u32::read(&number(7.0)), Some(7));
+        assert_eq!(u32::read(&number(-5.0)), None);
+        assert_eq!(i32::read(&number(3.7)), None);
+        assert_eq!(i32::read(&number(-3.0)), Some(-3));
+        assert_eq!(u8::read(&number(255.0)), Some(255));
+        assert_eq!(u8::read(&number(256.0)), None);
+        assert_eq!(i64::read(&number(i64::MIN as f64)), Some(i64::MIN));
+        assert_eq!(i64::read(&number(2f64.powi(63))), None);
+        assert_eq!(u64::read(&number(2f64.powi(64))), None);
+        assert_eq!(i32::read(&number(f64::NAN)), None);
+        assert_eq!(i32::read(&FieldValue::Number(None)), None);
+    }
+
+    #[test]
+    fn floats_reject_overflow() {
+        assert_eq!(f64::read(&number(3.7)), Some(3.7));
+        assert_eq!(f32::read(&number(3.5)), Some(3.5));
+        assert_eq!(f32::read(&number(1e300)), None);
+    }
+}
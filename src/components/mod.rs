@@ -24,3 +24,18 @@ pub use tooltip::Tooltip;
 
 pub mod segmented_control;
 pub use segmented_control::SegmentedControl;
+
+pub mod text_input;
+pub use text_input::TextInput;
+
+pub mod form;
+pub use form::{Field, Form};
+
+pub mod checkbox;
+pub use checkbox::Checkbox;
+
+pub mod switch;
+pub use switch::Switch;
+
+pub mod radio_group;
+pub use radio_group::RadioGroup;
@@ -0,0 +1,152 @@
+use dominator::{apply_methods, events, html, with_node, DomBuilder};
+use factoryizer::Factory;
+use futures_signals::signal::{Mutable, SignalExt};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::helpers::safety::rand_id;
+
+use super::{
+    checkbox::TOGGLE_CLASS,
+    form::Field,
+    ty::{Colour, Component, Length, Props, Reactive, Responsive},
+};
+
+#[derive(Default, Clone)]
+pub enum Orientation {
+    #[default]
+    Vertical,
+    Horizontal,
+}
+
+impl Orientation {
+    fn styles(&self) -> Vec<(&'static str, String)> {
+        let (direction, gap) = match self {
+            Orientation::Vertical => ("column", "0.5em"),
+            Orientation::Horizontal => ("row", "1em"),
+        };
+        vec![("flex-direction", direction.to_string()), ("gap", gap.to_string())]
+    }
+}
+
+/// Native radio buttons sharing a name, so arrow keys move between options
+#[derive(Factory, Default)]
+#[into]
+pub struct RadioGroup {
+    pub value: Mutable<String>,
+    /// Shown as the group's legend
+    label: Reactive<String>,
+    description: Reactive<String>,
+    error: Reactive<String>,
+    orientation: Responsive<Orientation>,
+    colour: Reactive<Colour>,
+    size: Reactive<Length>,
+    disabled: Reactive<bool>,
+
+    #[skip]
+    options: Vec<(String, String)>,
+    #[skip]
+    touched: Option<Mutable<bool>>,
+    #[skip]
+    props: Props,
+}
+
+impl RadioGroup {
+    /// Adds a `(label, value)` option
+    pub fn option(&mut self, option: (&str, &str)) -> &mut Self {
+        self.options.push((option.0.to_string(), option.1.to_string()));
+        self
+    }
+
+    /// Binds the value and error to a form field, touching it on change
+    pub fn field(&mut self, field: &Field<String>) -> &mut Self {
+        field.bind(&mut self.value, &mut self.error, &mut self.touched);
+        self
+    }
+}
+
+impl Component for RadioGroup {
+    fn props(&mut self) -> &mut Props {
+        &mut self.props
+    }
+
+    fn render(&mut self) -> DomBuilder<HtmlElement> {
+        let name = rand_id();
+        let description_id = format!("{}-description", name);
+        let error_id = format!("{}-error", name);
+
+        apply_methods!(DomBuilder::<HtmlElement>::new_html("fieldset"), {
+            .class("flex")
+            .class("flex-col")
+            .class("gap-1")
+            .class("border-0")
+            .class("p-0")
+            .class("m-0")
+            .attr("role", "radiogroup")
+            .attr("aria-describedby", &format!("{} {}", description_id, error_id))
+            .attr_signal("aria-invalid", self.error.signal().map(|error| (!error.is_empty()).then_some("true")))
+            .prop_signal("disabled", self.disabled.signal())
+            .style_signal("font-size", self.size.signal().map(|s| s.var()))
+            .style_signal("--domcom-toggle-colour", self.colour.signal().map(|c| c.var()))
+            .class_signal("opacity-60", self.disabled.signal())
+            .apply(|dom| match self.touched.take() {
+                Some(touched) => dom.event(move |_: events::Change| touched.set_neq(true)),
+                None => dom,
+            })
+            .child(html!("legend", {
+                .class("font-semibold")
+                .class("p-0")
+                .visible_signal(self.label.signal().map(|label| !label.is_empty()))
+                .text_signal(self.label.signal())
+            }))
+            .child(html!("p", {
+                .class("text-[0.85em]")
+                .class("opacity-75")
+                .attr("id", &description_id)
+                .visible_signal(self.description.signal().map(|description| !description.is_empty()))
+                .text_signal(self.description.signal())
+            }))
+            .child(html!("div", {
+                .class("flex")
+                .class(&self.orientation.class("", Orientation::styles))
+                .children(self.options.iter().map(|(label, option)| {
+                    html!("label", {
+                        .class("flex")
+                        .class("items-center")
+                        .class("gap-2")
+                        .child(html!("input" => HtmlInputElement, {
+                            .class(&*TOGGLE_CLASS)
+                            .attr("type", "radio")
+                            .attr("name", &name)
+                            .attr("value", option)
+                            .prop_signal("checked", self.value.signal_ref({
+                                let option = option.clone();
+                                move |value| *value == option
+                            }))
+                            .with_node!(input => {
+                                .event({
+                                    let value = self.value.clone();
+                                    move |_: events::Change| {
+                                        if input.checked() {
+                                            value.set_neq(input.value());
+                                        }
+                                    }
+                                })
+                            })
+                        }))
+                        .child(html!("span", {
+                            .text(label)
+                        }))
+                    })
+                }))
+            }))
+            .child(html!("p", {
+                .class("text-[0.85em]")
+                .attr("id", &error_id)
+                .attr("aria-live", "polite")
+                .style("color", &Colour::Danger.var())
+                .visible_signal(self.error.signal().map(|error| !error.is_empty()))
+                .text_signal(self.error.signal())
+            }))
+        })
+    }
+}
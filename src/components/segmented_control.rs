@@ -1,14 +1,22 @@
-use dominator::{class, events, html, pseudo, with_node, Dom};
+use dominator::{apply_methods, class, events, html, pseudo, with_node, DomBuilder};
 use factoryizer::Factory;
 use futures_signals::signal::{Mutable, SignalExt};
 use gloo_timers::callback::Timeout;
 use wasm_bindgen::JsCast;
-use web_sys::HtmlInputElement;
+use web_sys::{HtmlElement, HtmlInputElement};
 
-use crate::helpers::safety::{parent_or_self, rand_id};
+use crate::helpers::{
+    colours::{Rgba, WHITE},
+    safety::{parent_or_self, rand_id},
+    theme::check_contrast,
+    url_state::{self, UrlParam},
+};
+#[cfg(feature = "serde")]
+use crate::helpers::persist::{PersistKey, Persisted};
 
 use super::{
-    ty::{Colour, Reactive},
+    form::Field,
+    ty::{Colour, Props, Reactive},
     Component,
 };
 
@@ -22,7 +30,16 @@ pub struct SegmentedControl {
     #[skip]
     ready: Mutable<bool>,
     #[skip]
-    styles: Vec<(String, Reactive<String>)>,
+    props: Props,
+    #[skip]
+    error: Reactive<String>,
+    #[skip]
+    touched: Option<Mutable<bool>>,
+    #[cfg(feature = "serde")]
+    #[skip]
+    persist_key: Option<PersistKey>,
+    #[skip]
+    url_param: Option<UrlParam>,
 }
 
 impl SegmentedControl {
@@ -37,20 +54,76 @@ impl SegmentedControl {
             .push((option.0.to_string(), option.1.to_string()));
         self
     }
+    /// Mirrors the selected value to a URL parameter, see [`url_state::bind_with`]
+    pub fn sync_url(&mut self, param: impl Into<UrlParam>) -> &mut Self {
+        self.url_param = Some(param.into());
+        self
+    }
+
+    /// Binds the value to a form field, outlining the control while it has an error
+    pub fn field(&mut self, field: &Field<String>) -> &mut Self {
+        field.bind(&mut self.value, &mut self.error, &mut self.touched);
+        self
+    }
 }
 
-impl Component for SegmentedControl {
-    fn style(&mut self, style: (String, Reactive<String>)) -> &mut Self {
-        self.styles.push(style);
+#[cfg(feature = "serde")]
+impl SegmentedControl {
+    /// Restores the selected value from web storage on render and keeps it there
+    pub fn persist_key(&mut self, key: impl Into<PersistKey>) -> &mut Self {
+        self.persist_key = Some(key.into());
         self
     }
+}
+
+impl Component for SegmentedControl {
+    fn props(&mut self) -> &mut Props {
+        &mut self.props
+    }
+
+    fn render(&mut self) -> DomBuilder<HtmlElement> {
+        #[cfg(feature = "serde")]
+        let persist = self.persist_key.take().map(|key| Persisted::bind(key, self.value.clone()));
 
-    fn dom(&mut self) -> Dom {
-        if self.value.get_cloned().is_empty() && self.options.len() > 0 {
-            self.value = Mutable::new(self.options[0].0.clone());
+        let value = self.value.get_cloned();
+        if !self.options.iter().any(|(_, v)| *v == value) && !self.options.is_empty() {
+            self.value.set(self.options[0].1.clone());
         }
 
-        html!("div", {
+        let url_sync = self.url_param.take().map(|param| {
+            let values = self.options.iter().map(|(_, value)| value.clone()).collect::<Vec<String>>();
+            url_state::bind_with(param, self.value.clone(), String::clone, move |value| {
+                values.iter().find(|v| *v == value).cloned()
+            })
+        });
+
+        apply_methods!(DomBuilder::<HtmlElement>::new_html("div"), {
+            .after_inserted({
+                let colour = self.colour.clone();
+                move |e| check_contrast(&e, "SegmentedControl", |p, _| {
+                    Some((WHITE, Rgba::parse(&colour.resolve(p)).ok()?))
+                })
+            })
+            .apply(|dom| match url_sync {
+                Some(sync) => dom.future(sync),
+                None => dom,
+            })
+            .apply(|dom| {
+                #[cfg(feature = "serde")]
+                if let Some(persist) = persist {
+                    return dom.future(persist);
+                }
+                dom
+            })
+            .apply(|dom| match self.touched.take() {
+                Some(touched) => dom.event(move |_: events::Change| touched.set_neq(true)),
+                None => dom,
+            })
+            .attr_signal("aria-invalid", self.error.signal().map(|error| (!error.is_empty()).then_some("true")))
+            .attr_signal("title", self.error.signal().map(|error| (!error.is_empty()).then_some(error)))
+            .style_signal("outline", self.error.signal().map(|error| {
+                (!error.is_empty()).then(|| format!("2px solid {}", Colour::Danger.var()))
+            }))
             .class("flex")
             .child(
                 html!("div", {
@@ -80,7 +153,7 @@ impl Component for SegmentedControl {
                                 // Segment
                                 .pseudo!("::before", {
                                     .style("content", "''")
-                                    .style("background", self.colour.to_string())
+                                    .style("background", self.colour.var())
                                     .style("position", "absolute")
                                     .style("width", "var(--seg-w)")
                                     .style("transform", "translateX(var(--seg-x))")
@@ -121,15 +194,24 @@ impl Component for SegmentedControl {
                                                 move |value| value == v
                                             }))
                                             .with_node!(e => {
-                                                .event({
+                                                // Follows the value however it changes, e.g. from the URL, storage or a form reset
+                                                .future(self.value.signal_cloned().for_each({
                                                     let e = e.clone();
                                                     let offset_signal = self.offset.clone();
+                                                    let own_value = v.clone();
+                                                    move |value| {
+                                                        if value == own_value {
+                                                            let e = parent_or_self(e.clone());
+                                                            offset_signal.set((e.offset_left(), e.offset_width()));
+                                                        }
+                                                        async {}
+                                                    }
+                                                }))
+                                                .event({
                                                     let value_signal = self.value.clone();
                                                     let own_value = v.clone();
                                                     move |_: events::Change| {
-                                                        let e = parent_or_self(e.clone());
                                                         value_signal.set(own_value.clone());
-                                                        offset_signal.set((e.offset_left(), e.offset_width()));
                                                     }
                                                 })
                                                 .after_inserted({
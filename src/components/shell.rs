@@ -1,59 +1,364 @@
-use dominator::{html, with_node, Dom};
+use dominator::{apply_methods, events, html, with_node, Dom, DomBuilder};
 use factoryizer::Factory;
 use futures_signals::signal::{Mutable, SignalExt};
 use tabler_dominator::icon;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement};
 
 use crate::{
+    combine,
     components::{
         button::ButtonVariant,
         Button,
     },
-    helpers::safety::none_dom,
+    helpers::{
+        media::{media_query, media_query_signal},
+        router::{is_active_signal, Router},
+    },
 };
+#[cfg(feature = "serde")]
+use crate::helpers::persist::{PersistKey, Persisted};
 
-use super::ty::{Component, Reactive};
+use super::ty::{Breakpoint, Component, Props, Reactive};
 
 pub enum SidebarItem {
+    /// A plain link, shorthand for `Link(SidebarLink::new(text, href))`
     Item(&'static str, &'static str),
+    Link(SidebarLink),
+    Group(SidebarGroup),
     Title(&'static str),
     Spacer,
 }
 
+/// A sidebar link with an optional icon, badge count and disabled state
+pub struct SidebarLink {
+    text: &'static str,
+    href: &'static str,
+    icon: Option<Dom>,
+    badge: Option<Reactive<u32>>,
+    disabled: bool,
+}
+
+impl SidebarLink {
+    pub fn new(text: &'static str, href: &'static str) -> Self {
+        Self {
+            text,
+            href,
+            icon: None,
+            badge: None,
+            disabled: false,
+        }
+    }
+
+    /// e.g. `icon!("home")`
+    pub fn icon(mut self, icon: Dom) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// A count shown after the text, hidden while it is zero
+    pub fn badge(mut self, count: impl Into<Reactive<u32>>) -> Self {
+        self.badge = Some(count.into());
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+}
+
+/// A collapsible set of items, expanded by default
+pub struct SidebarGroup {
+    text: &'static str,
+    icon: Option<Dom>,
+    items: Vec<SidebarItem>,
+    pub expanded: Mutable<bool>,
+    #[cfg(feature = "serde")]
+    persist_key: Option<PersistKey>,
+}
+
+impl SidebarGroup {
+    pub fn new(text: &'static str) -> Self {
+        Self {
+            text,
+            icon: None,
+            items: Vec::new(),
+            expanded: Mutable::new(true),
+            #[cfg(feature = "serde")]
+            persist_key: None,
+        }
+    }
+
+    pub fn icon(mut self, icon: Dom) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    pub fn item(mut self, item: SidebarItem) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    pub fn items(mut self, items: Vec<SidebarItem>) -> Self {
+        self.items.extend(items);
+        self
+    }
+
+    pub fn expanded(self, expanded: bool) -> Self {
+        self.expanded.set(expanded);
+        self
+    }
+
+    /// Restores whether the group is expanded from web storage and keeps it there
+    #[cfg(feature = "serde")]
+    pub fn persist_key(mut self, key: impl Into<PersistKey>) -> Self {
+        self.persist_key = Some(key.into());
+        self
+    }
+}
+
+/// Icon, truncated text and badge, laid out in a row
+fn sidebar_row(text: &'static str, icon: Option<Dom>, badge: Option<&Reactive<u32>>) -> Vec<Dom> {
+    let mut row = Vec::new();
+    if let Some(icon) = icon {
+        row.push(html!("span", {
+            .class("w-5")
+            .class("h-5")
+            .class("shrink-0")
+            .child(icon)
+        }));
+    }
+    row.push(html!("span", {
+        .class("flex-1")
+        .class("min-w-0")
+        .class("truncate")
+        .attr("title", text)
+        .text(text)
+    }));
+    if let Some(badge) = badge {
+        row.push(html!("span", {
+            .class("shrink-0")
+            .class("px-2")
+            .class("rounded-full")
+            .class("text-xs")
+            .class("bg-white")
+            .class("text-black")
+            .visible_signal(badge.signal().map(|count| count > 0))
+            .text_signal(badge.signal().map(|count| count.to_string()))
+        }));
+    }
+    row
+}
+
+fn sidebar_items(items: &mut [SidebarItem], depth: usize) -> Vec<Dom> {
+    let indent = format!("{}rem", 0.5 + depth as f32);
+    items
+        .iter_mut()
+        .map(|item| match item {
+            SidebarItem::Item(text, href) => sidebar_link(&mut SidebarLink::new(text, href), &indent),
+            SidebarItem::Link(link) => sidebar_link(link, &indent),
+            SidebarItem::Group(group) => {
+                #[cfg(feature = "serde")]
+                let persist = group.persist_key.take().map(|key| Persisted::bind(key, group.expanded.clone()));
+
+                let expanded = group.expanded.clone();
+                html!("li", {
+                    .apply(|dom| {
+                        #[cfg(feature = "serde")]
+                        if let Some(persist) = persist {
+                            return dom.future(persist);
+                        }
+                        dom
+                    })
+                    .child(html!("button", {
+                        .class("flex")
+                        .class("items-center")
+                        .class("gap-2")
+                        .class("w-full")
+                        .class("mb-1")
+                        .class("pr-2")
+                        .style("padding-left", &indent)
+                        .attr_signal("aria-expanded", expanded.signal().map(|e| e.to_string()))
+                        .children(sidebar_row(group.text, group.icon.take(), None))
+                        .child(html!("span", {
+                            .class("shrink-0")
+                            .class("transition-transform")
+                            .style_signal("transform", expanded.signal().map(|e| if e { "rotate(90deg)" } else { "none" }))
+                            .child(icon!("chevron-right"))
+                        }))
+                        .event({
+                            let expanded = expanded.clone();
+                            move |_: events::Click| expanded.set(!expanded.get())
+                        })
+                    }))
+                    .child(html!("ul", {
+                        .visible_signal(expanded.signal())
+                        .children(sidebar_items(&mut group.items, depth + 1))
+                    }))
+                })
+            }
+            SidebarItem::Title(text) => html!("h2", {
+                .text(text)
+                .class("text-center")
+                .class("my-2")
+                .class("truncate")
+            }),
+            SidebarItem::Spacer => html!("hr", {}),
+        })
+        .collect()
+}
+
+fn sidebar_link(link: &mut SidebarLink, indent: &str) -> Dom {
+    let href = link.href;
+    html!("li", {
+        .child(html!("a", {
+            .class("flex")
+            .class("items-center")
+            .class("gap-2")
+            .class("mb-1")
+            .class("pr-2")
+            .style("padding-left", indent)
+            .apply(|dom| if link.disabled {
+                dom.attr("aria-disabled", "true")
+                    .class("opacity-50")
+                    .class("cursor-not-allowed")
+            } else {
+                dom.attr("href", href)
+                    .attr_signal("aria-current", is_active_signal(href).map(|active| active.then_some("page")))
+                    .class_signal("font-semibold", is_active_signal(href))
+                    .class_signal("underline", is_active_signal(href))
+            })
+            .children(sidebar_row(link.text, link.icon.take(), link.badge.as_ref()))
+        }))
+    })
+}
+
 #[derive(Factory, Default)]
 pub struct Shell {
     child: Option<Dom>,
     title: &'static str,
     sidebar: Vec<SidebarItem>,
+    /// Logo shown in the header, linking home
+    brand: Option<Dom>,
+    /// Shown on the right of the header
+    actions: Option<Dom>,
+    /// Pinned to the bottom of the sidebar
+    footer: Option<Dom>,
 
-    styles: Vec<(String, Reactive<String>)>,
+    #[skip]
+    props: Props,
     open: Mutable<SidebarState>,
+    #[skip]
+    router: Option<Router>,
+    #[cfg(feature = "serde")]
+    #[skip]
+    persist_key: Option<PersistKey>,
 }
 
 #[derive(Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SidebarState {
-    #[default]
     Open,
     Closed,
+    /// Open on desktop, closed on mobile where the sidebar is an overlay drawer
+    #[default]
     Default,
 }
 
-impl Component for Shell {
-    fn style(&mut self, style: (String, Reactive<String>)) -> &mut Self {
-        self.styles.push(style);
+impl SidebarState {
+    /// Whether the sidebar shows, `drawer` being true below the `Md` breakpoint
+    pub fn is_open(&self, drawer: bool) -> bool {
+        match self {
+            SidebarState::Open => true,
+            SidebarState::Closed => false,
+            SidebarState::Default => !drawer,
+        }
+    }
+}
+
+/// Matches viewports wide enough for the sidebar to sit beside the content
+fn drawer_query() -> String {
+    format!("(min-width: {}rem)", Breakpoint::Md.min_width())
+}
+
+fn drawer_width(drawer: bool) -> &'static str {
+    if drawer {
+        "var(--sidebar-open-width)"
+    } else {
+        "var(--sidebar-width)"
+    }
+}
+
+impl Shell {
+    /// Renders the matched route into the main area in place of `child`
+    pub fn router(&mut self, router: Router) -> &mut Self {
+        self.router = Some(router);
         self
     }
+}
+
+#[cfg(feature = "serde")]
+impl Shell {
+    /// Restores the sidebar state from web storage on render and keeps it there
+    pub fn persist_key(&mut self, key: impl Into<PersistKey>) -> &mut Self {
+        self.persist_key = Some(key.into());
+        self
+    }
+}
+
+impl Component for Shell {
+    fn props(&mut self) -> &mut Props {
+        &mut self.props
+    }
+
+    fn render(&mut self) -> DomBuilder<HtmlElement> {
+        #[cfg(feature = "serde")]
+        let persist = self.persist_key.take().map(|key| Persisted::bind(key, self.open.clone()));
 
-    fn dom(&mut self) -> dominator::Dom {
         let open = self.open.clone();
-        html!("div", {
+        let drawer = || media_query_signal(&drawer_query()).map(|wide| !wide);
+        let visible = {
+            let open = open.clone();
+            move || {
+                combine!(open.signal_cloned(), media_query_signal(&drawer_query()))
+                    .map(|(state, wide)| state.is_open(!wide))
+                    .dedupe()
+            }
+        };
+        let close_drawer = {
+            let open = open.clone();
+            move || {
+                if !media_query(&drawer_query()) {
+                    open.set(SidebarState::Closed);
+                }
+            }
+        };
+
+        apply_methods!(DomBuilder::<HtmlElement>::new_html("div"), {
+            .apply(|dom| {
+                #[cfg(feature = "serde")]
+                if let Some(persist) = persist {
+                    return dom.future(persist);
+                }
+                dom
+            })
             .class("flex")
             .class("flex-col")
             .class("w-screen")
             .class("h-screen")
             .class("fixed")
-            .style_signal("--sidebar-width", self.open.signal_cloned().map(|o| {
-                if o == SidebarState::Open { "var(--sidebar-open-width)" } else { "0" }
+            .style_signal("--sidebar-width", combine!(visible(), drawer()).map(|(visible, drawer)| {
+                if visible && !drawer { "var(--sidebar-open-width)" } else { "0" }
             }))
+            .global_event({
+                let close_drawer = close_drawer.clone();
+                move |e: events::KeyDown| {
+                    if e.key() == "Escape" {
+                        close_drawer();
+                    }
+                }
+            })
             .child(
                 html!("header", {
                     .class("text-white")
@@ -63,15 +368,15 @@ impl Component for Shell {
                     .class("flex")
                     .class("items-center")
                     .class("space-x-2")
-                    .child_signal(open.signal_cloned().map(move |o| {
+                    .child_signal(visible().map({
                         let open = open.clone();
-                        match o {
-                            SidebarState::Open => None,
-                            SidebarState::Closed | SidebarState::Default => Some(
-                                Button::new().variant(ButtonVariant::Subtle).child(icon!("menu-2")).on_click(move || {
+                        move |visible| {
+                            let open = open.clone();
+                            (!visible).then(|| {
+                                Button::new().variant(ButtonVariant::Subtle).child(icon!("menu-2")).aria("label", "Open sidebar").on_click(move || {
                                     open.set(SidebarState::Open)
                                 }).dom()
-                            )
+                            })
                         }
                     }))
                     .child(
@@ -81,10 +386,12 @@ impl Component for Shell {
                             .class("h-[var(--title-height)]")
                             .class("grid")
                             .class("place-items-center")
-                            // .child(
-                            //     Brand::new().variant(BrandVariant::Mark).style(("height".to_string(), "calc(100% - 1rem)".to_string().into())).dom()
-                            // )
                             .attr("href", "/")
+                            .attr("aria-label", "Home")
+                            .apply(|dom| match self.brand.take() {
+                                Some(brand) => dom.child(brand),
+                                None => dom,
+                            })
                         })
                     )
                     .child(
@@ -92,8 +399,34 @@ impl Component for Shell {
                             .text(&self.title)
                             .class("text-xl")
                             .class("font-semibold")
+                            .class("truncate")
                         })
                     )
+                    .apply(|dom| match self.actions.take() {
+                        Some(actions) => dom.child(html!("div", {
+                            .class("ml-auto")
+                            .class("pr-2")
+                            .class("flex")
+                            .class("items-center")
+                            .class("gap-2")
+                            .child(actions)
+                        })),
+                        None => dom,
+                    })
+                })
+            )
+            .child(
+                html!("div", {
+                    .class("fixed")
+                    .class("inset-0")
+                    .class("top-[var(--title-height)]")
+                    .class("z-30")
+                    .class("bg-black/50")
+                    .visible_signal(combine!(visible(), drawer()).map(|(visible, drawer)| visible && drawer))
+                    .event({
+                        let close_drawer = close_drawer.clone();
+                        move |_: events::Click| close_drawer()
+                    })
                 })
             )
             .child(
@@ -102,60 +435,59 @@ impl Component for Shell {
                     .class("flex")
                     .class("flex-col")
                     .class("flex-1")
-                    .class("w-[var(--sidebar-width)]")
-                    .class("max-w-[var(--sidebar-width)]")
                     .class("items-center")
                     .class("bg-black")
                     .class("overflow-y-auto")
                     .class("overflow-x-hidden")
+                    .class("transition-transform")
+                    .class("motion-reduce:transition-none")
+                    .style_signal("width", drawer().map(drawer_width))
+                    .style_signal("max-width", drawer().map(drawer_width))
+                    // Drawer mode lays the sidebar over the content instead of beside it
+                    .style_signal("position", drawer().map(|drawer| drawer.then_some("fixed")))
+                    .style_signal("top", drawer().map(|drawer| drawer.then_some("var(--title-height)")))
+                    .style_signal("bottom", drawer().map(|drawer| drawer.then_some("0")))
+                    .style_signal("left", drawer().map(|drawer| drawer.then_some("0")))
+                    .style_signal("z-index", drawer().map(|drawer| drawer.then_some("40")))
+                    .style_signal("transform", visible().map(|visible| (!visible).then_some("translateX(-100%)")))
+                    .attr_signal("inert", visible().map(|visible| (!visible).then_some("")))
+                    .event({
+                        let close_drawer = close_drawer.clone();
+                        move |e: events::Click| {
+                            let link = e
+                                .target()
+                                .and_then(|target| target.dyn_into::<Element>().ok())
+                                .and_then(|target| target.closest("a[href]").ok().flatten());
+                            if link.is_some() {
+                                close_drawer();
+                            }
+                        }
+                    })
                     .child(html!("ul", {
                         .class("w-full")
-                        .class("overflow-x-auto") // TODO: Make this elipsis
-                        .children(
-                            self.sidebar.iter_mut().map(|item| {
-                                match item {
-                                    SidebarItem::Item(text, link) => {
-                                       html!("li", {
-                                            .child( html!("a", {
-                                                .text(text)
-                                                .attr("href", link)
-                                                .class("pl-2")
-                                                .class("mb-1")
-                                                .class("w-[calc(100%-1rem)]")
-                                            }))
-                                       })
-                                    }
-                                    SidebarItem::Title(text) => {
-                                        html!("h2", {
-                                            .text(text)
-                                            .class("text-center")
-                                            .class("my-2")
-                                        })
-                                    }
-                                    SidebarItem::Spacer => {
-                                        html!("hr", {})
-                                    }
-                                }
-                            })
-                        )
+                        .children(sidebar_items(&mut self.sidebar, 0))
                     }))
-                    .child(Button::new().variant(ButtonVariant::Subtle).child(icon!("x")).on_click({
+                    .apply(|dom| match self.footer.take() {
+                        Some(footer) => dom.child(html!("div", {
+                            .class("mt-auto")
+                            .class("w-full")
+                            .child(footer)
+                        })),
+                        None => dom,
+                    })
+                    .child(Button::new().variant(ButtonVariant::Subtle).child(icon!("x")).aria("label", "Close sidebar").on_click({
                         let open = self.open.clone();
-                        move || {
-                            open.set({
-                                if open.get_cloned() == SidebarState::Open {
-                                    SidebarState::Closed
-                                } else {
-                                    SidebarState::Open
-                                }
-                            });
-                        }
+                        move || open.set(SidebarState::Closed)
                     }).dom())
                 }
             ))
             .child(
                 html!("main", {
-                    .child(self.child.take().unwrap_or_else(|| none_dom()))
+                    .apply(|dom| match (&self.router, self.child.take()) {
+                        (Some(router), _) => dom.child(router.dom()),
+                        (None, Some(child)) => dom.child(child),
+                        (None, None) => dom,
+                    })
                     .class("absolute")
                     .class("left-0")
                     .class("top-0")
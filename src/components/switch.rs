@@ -0,0 +1,112 @@
+use dominator::{class, events, html, pseudo, with_node, DomBuilder};
+use factoryizer::Factory;
+use futures_signals::signal::{Mutable, SignalExt};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::helpers::safety::rand_id;
+
+use super::{
+    checkbox::labelled,
+    form::Field,
+    ty::{Colour, Component, Length, Props, Reactive},
+};
+
+lazy_static::lazy_static! {
+    // A native checkbox drawn as a track and thumb, so keyboard and form behaviour stay native
+    static ref SWITCH_CLASS: String = class! {
+        .style("appearance", "none")
+        .style("position", "relative")
+        .style("flex-shrink", "0")
+        .style("width", "2em")
+        .style("height", "1.15em")
+        .style("margin", "0")
+        .style("border-radius", "999px")
+        .style("background", "var(--domcom-switch-off)")
+        .style("cursor", "pointer")
+        .style("transition", "background 150ms")
+        .pseudo!("::before", {
+            .style("content", "\"\"")
+            .style("position", "absolute")
+            .style("top", "0.15em")
+            .style("left", "0.15em")
+            .style("width", "0.85em")
+            .style("height", "0.85em")
+            .style("border-radius", "50%")
+            .style("background", "#ffffff")
+            .style("transition", "transform 150ms")
+        })
+        .pseudo!(":checked", {
+            .style("background", "var(--domcom-switch-on)")
+        })
+        .pseudo!(":checked::before", {
+            .style("transform", "translateX(0.85em)")
+        })
+        .pseudo!(":focus-visible", {
+            .style("outline", "2px solid var(--domcom-switch-on)")
+            .style("outline-offset", "2px")
+        })
+        .pseudo!(":disabled", {
+            .style("cursor", "not-allowed")
+        })
+    };
+}
+
+#[derive(Factory, Default)]
+#[into]
+pub struct Switch {
+    pub checked: Mutable<bool>,
+    label: Reactive<String>,
+    description: Reactive<String>,
+    error: Reactive<String>,
+    colour: Reactive<Colour>,
+    size: Reactive<Length>,
+    disabled: Reactive<bool>,
+
+    #[skip]
+    touched: Option<Mutable<bool>>,
+    #[skip]
+    props: Props,
+}
+
+impl Switch {
+    /// Binds the checked state and error to a form field, touching it on change
+    pub fn field(&mut self, field: &Field<bool>) -> &mut Self {
+        field.bind(&mut self.checked, &mut self.error, &mut self.touched);
+        self
+    }
+}
+
+impl Component for Switch {
+    fn props(&mut self) -> &mut Props {
+        &mut self.props
+    }
+
+    fn render(&mut self) -> DomBuilder<HtmlElement> {
+        let id = rand_id();
+        let control = html!("input" => HtmlInputElement, {
+            .class(&*SWITCH_CLASS)
+            .attr("type", "checkbox")
+            .attr("role", "switch")
+            .attr("aria-describedby", &format!("{0}-description {0}-error", id))
+            .attr_signal("aria-invalid", self.error.signal().map(|error| (!error.is_empty()).then_some("true")))
+            .style_signal("--domcom-switch-on", self.colour.signal().map(|c| c.var()))
+            .style("--domcom-switch-off", &Colour::Grey.shade(300))
+            .prop_signal("checked", self.checked.signal())
+            .prop_signal("disabled", self.disabled.signal())
+            .with_node!(input => {
+                .event({
+                    let checked = self.checked.clone();
+                    let touched = self.touched.take();
+                    move |_: events::Change| {
+                        checked.set_neq(input.checked());
+                        if let Some(touched) = &touched {
+                            touched.set_neq(true);
+                        }
+                    }
+                })
+            })
+        });
+
+        labelled(&id, control, &self.label, &self.description, &self.error, &self.size, &self.disabled)
+    }
+}
@@ -1,19 +1,26 @@
-use dominator::{class, html, pseudo, with_node};
+use dominator::{apply_methods, class, html, pseudo, with_node, DomBuilder};
 use factoryizer::Factory;
 use futures_signals::signal::{Mutable, SignalExt};
 use tabler_dominator::icon;
+use web_sys::HtmlElement;
 
-use crate::helpers::{
-    colours::{bw_on_bg, opacity},
-    mutable::Mutable2,
+use crate::{
+    combine,
+    helpers::{
+        colours::{best_text_on_translucent, css_opacity, Rgba, TRANSLUCENT},
+        theme::check_contrast,
+        url_state::{self, UrlParam},
+    },
 };
+#[cfg(feature = "serde")]
+use crate::helpers::persist::{PersistKey, Persisted};
 
 use super::{
-    ty::{Colour, Reactive},
+    ty::{Colour, Props},
     Component,
 };
 
-type SortFunction = fn(&str, &str) -> SortMovement;
+pub type SortFunction = fn(&str, &str) -> SortMovement;
 
 #[derive(Clone, Default)]
 pub enum TableDirection {
@@ -23,6 +30,7 @@ pub enum TableDirection {
 }
 
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SortDirection {
     Ascending,
     Descending,
@@ -74,34 +82,89 @@ pub fn alphabetical_sort(a: &str, b: &str) -> SortMovement {
     }
 }
 
+/// A value shown in a table cell, see `#[derive(DomcomTable)]`
+pub trait TableCell {
+    fn cell(&self) -> String;
+    fn sort() -> Option<SortFunction>;
+}
+
+macro_rules! table_cells {
+    ($sort:ident => $($ty:ty),*) => {
+        $(impl TableCell for $ty {
+            fn cell(&self) -> String {
+                self.to_string()
+            }
+
+            fn sort() -> Option<SortFunction> {
+                Some($sort)
+            }
+        })*
+    };
+}
+
+table_cells!(numerical_sort => i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+table_cells!(alphabetical_sort => String, &str, char, bool);
+
+impl<T: TableCell> TableCell for Option<T> {
+    fn cell(&self) -> String {
+        self.as_ref().map(T::cell).unwrap_or_default()
+    }
+
+    fn sort() -> Option<SortFunction> {
+        T::sort()
+    }
+}
+
+/// A struct shown as one row of a table, usually derived with `#[derive(DomcomTable)]`
+pub trait TableRow {
+    fn columns(rows: &[Self]) -> Vec<TableValues>
+    where
+        Self: Sized;
+}
+
 #[derive(Factory, Clone, Default)]
 pub struct Table {
     pub data: Mutable<Vec<TableValues>>,
     pub direction: TableDirection,
     colour: Colour,
-    styles: Vec<(String, Reactive<String>)>,
+    #[skip]
+    props: Props,
 
     #[skip]
     applied_sort: Mutable<(String, SortDirection)>,
+    #[cfg(feature = "serde")]
+    #[skip]
+    persist_key: Option<PersistKey>,
+    #[skip]
+    url_param: Option<UrlParam>,
 }
 
 impl Table {
-    pub fn sort_data(data: Vec<TableValues>, sort: (String, SortDirection)) -> Vec<TableValues> {
-        let sort_values = data.iter().find(|container| container.title == sort.0);
+    /// Replaces the data with a column per field of `T`
+    pub fn rows<T: TableRow>(&mut self, rows: &[T]) -> &mut Self {
+        self.data.set(T::columns(rows));
+        self
+    }
 
-        if sort_values.is_none() || sort.1 == SortDirection::None {
+    pub fn sort_data(data: Vec<TableValues>, sort: (String, SortDirection)) -> Vec<TableValues> {
+        if sort.1 == SortDirection::None {
             return data;
         }
 
-        let sort_values = sort_values.clone().unwrap();
+        // Unknown or unsortable columns, e.g. from an old link, leave the data as is
+        let Some(sort_values) = data.iter().find(|container| container.title == sort.0) else {
+            return data;
+        };
+        let Some(sort_function) = sort_values.sort else {
+            return data;
+        };
+
         let mut sort_map = sort_values
             .values
             .iter()
             .enumerate()
-            .map(|(i, value)| (i, value))
             .collect::<Vec<(usize, &String)>>();
 
-        let sort_function = sort_values.sort.unwrap();
         sort_map.sort_by(|a, b| match sort_function(a.1, b.1) {
             SortMovement::Up => std::cmp::Ordering::Greater,
             SortMovement::Down => std::cmp::Ordering::Less,
@@ -110,13 +173,15 @@ impl Table {
 
         // Use indexes of sort map to sort data
         let mut sorted_data = Vec::new();
-        for i in 0..data.len() {
-            let mut container = TableValues::default();
-            container.title = data[i].title.clone();
-            container.sort = data[i].sort.clone();
+        for values in data.iter() {
+            let mut container = TableValues {
+                title: values.title.clone(),
+                sort: values.sort,
+                ..Default::default()
+            };
 
             for (index, _) in sort_map.iter() {
-                container.values.push(data[i].values[*index].clone());
+                container.values.push(values.values[*index].clone());
             }
 
             if sort.1 == SortDirection::Descending {
@@ -128,19 +193,82 @@ impl Table {
 
         sorted_data
     }
+
+    /// Mirrors the sort to a URL parameter as `column` or `-column` for descending,
+    /// see [`url_state::bind_with`]
+    pub fn sync_url(&mut self, param: impl Into<UrlParam>) -> &mut Self {
+        self.url_param = Some(param.into());
+        self
+    }
+
+    fn encode_sort((column, direction): &(String, SortDirection)) -> String {
+        match direction {
+            SortDirection::Ascending => column.clone(),
+            SortDirection::Descending => format!("-{}", column),
+            SortDirection::None => String::new(),
+        }
+    }
+
+    /// Any column is accepted, rows may still be loading and
+    /// [`sort_data`](Self::sort_data) ignores columns it can't sort by
+    fn decode_sort(value: &str) -> Option<(String, SortDirection)> {
+        let (column, direction) = match value.strip_prefix('-') {
+            Some(column) => (column, SortDirection::Descending),
+            None => (value, SortDirection::Ascending),
+        };
+        (!column.is_empty()).then(|| (column.to_string(), direction))
+    }
 }
 
-impl Component for Table {
-    fn style(&mut self, style: (String, Reactive<String>)) -> &mut Self {
-        self.styles.push(style);
+#[cfg(feature = "serde")]
+impl Table {
+    /// Restores the sort column and direction from web storage on render and keeps it there
+    pub fn persist_key(&mut self, key: impl Into<PersistKey>) -> &mut Self {
+        self.persist_key = Some(key.into());
         self
     }
-    fn dom(&mut self) -> dominator::Dom {
-        html!("table", {
-            .child_signal(Mutable2::new(self.data.clone(), self.applied_sort.clone()).map({
+}
+
+impl Component for Table {
+    fn props(&mut self) -> &mut Props {
+        &mut self.props
+    }
+    fn render(&mut self) -> DomBuilder<HtmlElement> {
+        // Stored sorts aren't checked against the data, which may not have loaded yet
+        #[cfg(feature = "serde")]
+        let persist = self.persist_key.take().map(|key| Persisted::bind(key, self.applied_sort.clone()));
+
+        let url_sync = self
+            .url_param
+            .take()
+            .map(|param| url_state::bind_with(param, self.applied_sort.clone(), Self::encode_sort, Self::decode_sort));
+
+        apply_methods!(DomBuilder::<HtmlElement>::new_html("table"), {
+            .after_inserted({
+                let colour = self.colour.clone();
+                move |e| check_contrast(&e, "Table", |p, scheme| {
+                    let c = Rgba::parse(&colour.resolve(p)).ok()?;
+                    Some((best_text_on_translucent(c, scheme.page_colour()), c.with_alpha(c.alpha() * TRANSLUCENT)))
+                })
+            })
+            .apply(|dom| match url_sync {
+                Some(sync) => dom.future(sync),
+                None => dom,
+            })
+            .apply(|dom| {
+                #[cfg(feature = "serde")]
+                if let Some(persist) = persist {
+                    return dom.future(persist);
+                }
+                dom
+            })
+            .child_signal(combine!(self.data.signal_cloned(), self.applied_sort.signal_cloned()).map({
                 let direction = self.direction.clone();
                 let applied_sort = self.applied_sort.clone();
-                let colour = self.colour.clone();
+                let colour = self.colour.var();
+                let translucent = self.colour.translucent();
+                let contrast = self.colour.translucent_contrast_var();
+                let hover = self.colour.shade(100);
                 move |(data, sort)| {
 
                     let data = Self::sort_data(data, sort.clone());
@@ -175,15 +303,15 @@ impl Component for Table {
                         .class(
                             class! {
                                 .pseudo!("> tr:nth-child(odd)", {
-                                    .style("background", opacity(colour.to_string(), 0.15))
+                                    .style("background", css_opacity(&colour, 0.15))
                                 })
                             }
                         )
                         .child(html!("tr", {
                             .class(
                                 class! {
-                                    .style_important("background", opacity(colour.to_string(), 0.75))
-                                    .style("color", bw_on_bg(opacity(colour.to_string(), 0.75)))
+                                    .style_important("background", &translucent)
+                                    .style("color", &contrast)
                                 }
                             )
                             .children(data.iter().map(|container| {
@@ -242,7 +370,7 @@ impl Component for Table {
                                 .class(
                                     class! {
                                         .pseudo!(":hover", {
-                                            .style_important("background", opacity(colour.to_string(), 0.25))
+                                            .style_important("background", &hover)
                                         })
                                     }
                                 )
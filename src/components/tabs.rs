@@ -1,10 +1,17 @@
-use dominator::{class, html, pseudo, with_node, Dom};
+use dominator::{apply_methods, class, html, pseudo, with_node, Dom, DomBuilder};
 use factoryizer::Factory;
 use futures_signals::signal::{Mutable, SignalExt};
+use web_sys::HtmlElement;
 
-use crate::helpers::colours::{bw_on_bg, opacity};
+use crate::helpers::{
+    colours::{best_text_on_translucent, Rgba, TRANSLUCENT},
+    theme::check_contrast,
+    url_state::{self, UrlParam},
+};
+#[cfg(feature = "serde")]
+use crate::helpers::persist::{PersistKey, Persisted};
 
-use super::ty::{Colour, Component, Reactive};
+use super::ty::{Colour, Component, Props, Responsive};
 
 #[derive(Default, Clone)]
 pub enum TabPlacement {
@@ -15,6 +22,47 @@ pub enum TabPlacement {
     Right,
 }
 
+impl TabPlacement {
+    fn root_styles(&self) -> Vec<(&'static str, String)> {
+        let direction = match self {
+            TabPlacement::Top => "column",
+            TabPlacement::Bottom => "column-reverse",
+            TabPlacement::Left => "row",
+            TabPlacement::Right => "row-reverse",
+        };
+        vec![("flex-direction", direction.to_string())]
+    }
+
+    fn list_styles(&self) -> Vec<(&'static str, String)> {
+        let (direction, gap) = match self {
+            TabPlacement::Top | TabPlacement::Bottom => ("row", "1rem"),
+            TabPlacement::Left | TabPlacement::Right => ("column", "0.5rem"),
+        };
+        let (margin, border) = match self {
+            TabPlacement::Top => ("0 0 0.5rem 0", "0 0 1px 0"),
+            TabPlacement::Bottom => ("0.5rem 0 0 0", "1px 0 0 0"),
+            TabPlacement::Left => ("0 0.5rem 0 0", "0 1px 0 0"),
+            TabPlacement::Right => ("0 0 0 0.5rem", "0 0 0 1px"),
+        };
+        vec![
+            ("flex-direction", direction.to_string()),
+            ("gap", gap.to_string()),
+            ("margin", margin.to_string()),
+            ("border-width", border.to_string()),
+        ]
+    }
+
+    fn tab_styles(&self) -> Vec<(&'static str, String)> {
+        let radius = match self {
+            TabPlacement::Top => "0.25rem 0.25rem 0 0",
+            TabPlacement::Bottom => "0 0 0.25rem 0.25rem",
+            TabPlacement::Left => "0.25rem 0 0 0.25rem",
+            TabPlacement::Right => "0 0.25rem 0.25rem 0",
+        };
+        vec![("border-radius", radius.to_string())]
+    }
+}
+
 pub struct Tab {
     title: &'static str,
     id: &'static str,
@@ -28,14 +76,21 @@ impl Tab {
 }
 
 #[derive(Factory, Default)]
+#[into]
 pub struct Tabs {
     tabs: Vec<Tab>,
-    styles: Vec<(String, Reactive<String>)>,
-    placement: TabPlacement,
+    #[skip]
+    props: Props,
+    placement: Responsive<TabPlacement>,
     colour: Colour,
 
     #[skip]
     pub selected: Mutable<String>,
+    #[cfg(feature = "serde")]
+    #[skip]
+    persist_key: Option<PersistKey>,
+    #[skip]
+    url_param: Option<UrlParam>,
 }
 
 impl Tabs {
@@ -43,50 +98,69 @@ impl Tabs {
         self.tabs.push(child);
         self
     }
+    /// Mirrors the selected tab to a URL parameter, see [`url_state::bind_with`]
+    pub fn sync_url(&mut self, param: impl Into<UrlParam>) -> &mut Self {
+        self.url_param = Some(param.into());
+        self
+    }
 }
 
-impl Component for Tabs {
-    fn style(&mut self, style: (String, Reactive<String>)) -> &mut Self {
-        self.styles.push(style);
+#[cfg(feature = "serde")]
+impl Tabs {
+    /// Restores the selected tab from web storage on render and keeps it there
+    pub fn persist_key(&mut self, key: impl Into<PersistKey>) -> &mut Self {
+        self.persist_key = Some(key.into());
         self
     }
-    fn dom(&mut self) -> dominator::Dom {
-        if self.selected.get_cloned().is_empty() && self.tabs.len() > 0 {
+}
+
+impl Component for Tabs {
+    fn props(&mut self) -> &mut Props {
+        &mut self.props
+    }
+    fn render(&mut self) -> DomBuilder<HtmlElement> {
+        #[cfg(feature = "serde")]
+        let persist = self.persist_key.take().map(|key| Persisted::bind(key, self.selected.clone()));
+
+        let selected = self.selected.get_cloned();
+        if !self.tabs.iter().any(|tab| tab.id == selected) && !self.tabs.is_empty() {
             self.selected.set(self.tabs[0].id.to_string());
         }
 
-        html!("div", {
-            .class("flex")
-            .class(match self.placement {
-                TabPlacement::Top => "flex-col",
-                TabPlacement::Bottom => "flex-col-reverse",
-                TabPlacement::Left => "flex-row",
-                TabPlacement::Right => "flex-row-reverse",
+        let url_sync = self.url_param.take().map(|param| {
+            let ids = self.tabs.iter().map(|tab| tab.id.to_string()).collect::<Vec<String>>();
+            url_state::bind_with(param, self.selected.clone(), String::clone, move |id| {
+                ids.iter().find(|tab| *tab == id).cloned()
+            })
+        });
+
+        apply_methods!(DomBuilder::<HtmlElement>::new_html("div"), {
+            .after_inserted({
+                let colour = self.colour.clone();
+                move |e| check_contrast(&e, "Tabs", |p, scheme| {
+                    let c = Rgba::parse(&colour.resolve(p)).ok()?;
+                    Some((best_text_on_translucent(c, scheme.page_colour()), c.with_alpha(c.alpha() * TRANSLUCENT)))
+                })
             })
+            .apply(|dom| match url_sync {
+                Some(sync) => dom.future(sync),
+                None => dom,
+            })
+            .apply(|dom| {
+                #[cfg(feature = "serde")]
+                if let Some(persist) = persist {
+                    return dom.future(persist);
+                }
+                dom
+            })
+            .class("flex")
+            .class(&self.placement.class("", TabPlacement::root_styles))
             .child(html!("div", {
                 .class("flex")
-                .class(match self.placement {
-                    TabPlacement::Top | TabPlacement::Bottom => "flex-row",
-                    TabPlacement::Left | TabPlacement::Right => "flex-col",
-                })
-                .class(match self.placement {
-                    TabPlacement::Top | TabPlacement::Bottom => "space-x-4",
-                    TabPlacement::Left | TabPlacement::Right => "space-y-2",
-                })
-                .class(match self.placement {
-                    TabPlacement::Top => "mb-2",
-                    TabPlacement::Bottom => "mt-2",
-                    TabPlacement::Left => "mr-2",
-                    TabPlacement::Right => "ml-2",
-                })
-                .class(match self.placement {
-                    TabPlacement::Top => "border-b",
-                    TabPlacement::Bottom => "border-t",
-                    TabPlacement::Left => "border-r",
-                    TabPlacement::Right => "border-l",
-                })
+                .class(&self.placement.class("", TabPlacement::list_styles))
                 .class("border-neutral-300")
                 .apply(|mut d| {
+                    let tab_class = self.placement.class("", TabPlacement::tab_styles);
                     let selected = self.selected.clone();
                     for tab in &self.tabs {
                         let selected = selected.clone();
@@ -97,43 +171,59 @@ impl Component for Tabs {
                             .class("font-semibold")
                             .class("px-4")
                             .class("py-2")
-                            .class(match self.placement {
-                                TabPlacement::Top => "rounded-t",
-                                TabPlacement::Bottom => "rounded-b",
-                                TabPlacement::Left => "rounded-l",
-                                TabPlacement::Right => "rounded-r",
-                            })
+                            .class(&tab_class)
                             .class(
                                 class! {
                                     .style_signal("color", selected.signal_cloned().map({
-                                        let colour = self.colour.to_string();
+                                        let colour = self.colour.clone();
                                         move |s| {
                                             if s == id {
-                                                bw_on_bg(colour.clone())
+                                                colour.translucent_contrast_var()
                                             } else {
-                                                colour.clone()
+                                                colour.var()
                                             }
                                         }
                                     }))
                                     .style_signal("background", selected.signal_cloned().map({
-                                        let colour = self.colour.to_string();
+                                        let colour = self.colour.clone();
                                         move |s| {
                                             if s == id {
-                                                opacity(colour.clone(), 0.75)
+                                                colour.translucent()
                                             } else {
                                                 "transparent".to_string()
                                             }
                                         }
                                     }))
                                     .pseudo!(":hover", {
-                                        .style("color", bw_on_bg(self.colour.to_string()))
+                                        .style_signal("color", selected.signal_cloned().map({
+                                            let colour = self.colour.clone();
+                                            move |s| {
+                                                if s != id {
+                                                    colour.shade(700)
+                                                } else {
+                                                    colour.contrast_var()
+                                                }
+                                            }
+                                        }))
+                                        .style_signal("background", selected.signal_cloned().map({
+                                            let colour = self.colour.clone();
+                                            move |s| {
+                                                if s != id {
+                                                    colour.shade(100)
+                                                } else {
+                                                    colour.shade(600)
+                                                }
+                                            }
+                                        }))
+                                    })
+                                    .pseudo!(":active", {
                                         .style_signal("background", selected.signal_cloned().map({
-                                            let colour = self.colour.to_string();
+                                            let colour = self.colour.clone();
                                             move |s| {
                                                 if s != id {
-                                                    opacity(colour.clone(), 0.5)
+                                                    colour.shade(200)
                                                 } else {
-                                                    opacity(colour.clone(), 0.75) // TODO: Can this just be blank?
+                                                    colour.shade(700)
                                                 }
                                             }
                                         }))
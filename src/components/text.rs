@@ -1,7 +1,14 @@
-use dominator::{class, html};
+use dominator::{apply_methods, class, with_node, DomBuilder};
 use factoryizer::Factory;
+use futures_signals::signal::SignalExt;
+use web_sys::HtmlElement;
 
-use super::ty::{Component, Reactive, TextColour};
+use crate::helpers::{
+    colours::{Rgba, BLACK},
+    theme::check_contrast,
+};
+
+use super::ty::{Component, Props, Reactive, TextColour};
 
 #[derive(Default, Clone)]
 pub enum TextVariant {
@@ -17,22 +24,22 @@ pub enum TextVariant {
 }
 
 #[derive(Factory, Default)]
+#[into]
 pub struct Text {
     text: &'static str,
     variant: TextVariant,
-    colour: TextColour,
+    colour: Reactive<TextColour>,
 
-    styles: Vec<(String, Reactive<String>)>,
-    id: Option<String>,
+    #[skip]
+    props: Props,
 }
 
 impl Component for Text {
-    fn style(&mut self, style: (String, Reactive<String>)) -> &mut Self {
-        self.styles.push(style);
-        self
+    fn props(&mut self) -> &mut Props {
+        &mut self.props
     }
-    fn dom(&mut self) -> dominator::Dom {
-        html!({
+    fn render(&mut self) -> DomBuilder<HtmlElement> {
+        apply_methods!(DomBuilder::<HtmlElement>::new_html({
             // This could all be a <span> but
             // samantics are important for accesibility
             // which I am aiming to make a priority
@@ -45,10 +52,16 @@ impl Component for Text {
                 TextVariant::H2 => "h2",
                 TextVariant::H1 => "h1"
             }
-        }, {
+        }), {
+            .with_node!(e => {
+                .future(self.colour.signal().for_each(move |colour| {
+                    check_contrast(&e, "Text", |p, _| Some((Rgba::parse(&colour.resolve(p)).ok()?, BLACK.with_alpha(0.0))));
+                    async {}
+                }))
+            })
+            .style_signal("color", self.colour.signal().map(|colour| colour.var()))
             .class(
                 class! {
-                    .style("color", &self.colour.to_string())
                     .style(
                         "font-size",
                         &match self.variant {
@@ -78,13 +91,6 @@ impl Component for Text {
                 }
             )
             .text(&self.text)
-            .attr("id", &self.id.clone().unwrap_or_default())
-            .apply(|mut d| {
-                for (k, v) in self.styles.iter() {
-                    d = v.apply_style(k.to_string(), d);
-                }
-                d
-            })
         })
     }
 }
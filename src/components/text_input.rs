@@ -0,0 +1,176 @@
+use dominator::{apply_methods, class, events, html, pseudo, with_node, Dom, DomBuilder};
+use factoryizer::Factory;
+use futures_signals::{
+    map_ref,
+    signal::{Mutable, SignalExt},
+};
+use web_sys::{HtmlElement, HtmlInputElement};
+
+use crate::helpers::safety::rand_id;
+
+use super::{
+    form::{Field, FieldKind},
+    ty::{Colour, Component, Length, Props, Reactive},
+};
+
+lazy_static::lazy_static! {
+    static ref INPUT_CLASS: String = class! {
+        .style("border", "1px solid var(--domcom-input-border)")
+        .pseudo!(":focus-within", {
+            .style("border-color", "var(--domcom-input-accent)")
+            .style("box-shadow", "0 0 0 1px var(--domcom-input-accent)")
+        })
+    };
+}
+
+#[derive(Factory, Default)]
+#[into]
+pub struct TextInput {
+    pub value: Mutable<String>,
+    /// The `type` attribute, `text` by default
+    input_type: &'static str,
+    label: Reactive<String>,
+    description: Reactive<String>,
+    placeholder: Reactive<String>,
+    /// Shown under the input, which turns red while it is not empty
+    error: Reactive<String>,
+    colour: Reactive<Colour>,
+    size: Reactive<Length>,
+    radius: Reactive<Length>,
+    disabled: Reactive<bool>,
+    readonly: Reactive<bool>,
+
+    #[skip]
+    left_section: Option<Dom>,
+    #[skip]
+    right_section: Option<Dom>,
+    #[skip]
+    touched: Option<Mutable<bool>>,
+    #[skip]
+    props: Props,
+}
+
+impl TextInput {
+    /// Shown inside the input before the text, e.g. `icon!("search")`
+    pub fn left_section(&mut self, section: Dom) -> &mut Self {
+        self.left_section = Some(section);
+        self
+    }
+
+    /// Shown inside the input after the text
+    pub fn right_section(&mut self, section: Dom) -> &mut Self {
+        self.right_section = Some(section);
+        self
+    }
+
+    /// Binds the value and error to a form field, touching it on blur
+    pub fn field(&mut self, field: &Field<String>) -> &mut Self {
+        field.bind(&mut self.value, &mut self.error, &mut self.touched);
+        if field.kind() == FieldKind::Number && self.input_type.is_empty() {
+            self.input_type = "number";
+        }
+        self
+    }
+}
+
+fn section(section: Option<Dom>) -> Option<Dom> {
+    section.map(|section| {
+        html!("span", {
+            .class("flex")
+            .class("items-center")
+            .class("shrink-0")
+            .class("w-[1.25em]")
+            .class("h-[1.25em]")
+            .class("opacity-75")
+            .child(section)
+        })
+    })
+}
+
+impl Component for TextInput {
+    fn props(&mut self) -> &mut Props {
+        &mut self.props
+    }
+
+    fn render(&mut self) -> DomBuilder<HtmlElement> {
+        let id = rand_id();
+        let description_id = format!("{}-description", id);
+        let error_id = format!("{}-error", id);
+        let invalid = || self.error.signal().map(|error| !error.is_empty());
+
+        apply_methods!(DomBuilder::<HtmlElement>::new_html("div"), {
+            .class("flex")
+            .class("flex-col")
+            .class("gap-1")
+            .style_signal("font-size", self.size.signal().map(|s| s.var()))
+            .child(html!("label", {
+                .class("font-semibold")
+                .attr("for", &id)
+                .visible_signal(self.label.signal().map(|label| !label.is_empty()))
+                .text_signal(self.label.signal())
+            }))
+            .child(html!("p", {
+                .class("text-[0.85em]")
+                .class("opacity-75")
+                .attr("id", &description_id)
+                .visible_signal(self.description.signal().map(|description| !description.is_empty()))
+                .text_signal(self.description.signal())
+            }))
+            .child(html!("div", {
+                .class("flex")
+                .class("items-center")
+                .class("gap-2")
+                .class(&*INPUT_CLASS)
+                .style_signal("--domcom-input-border", invalid().map(|invalid| {
+                    if invalid { Colour::Danger.var() } else { Colour::Grey.shade(300) }
+                }))
+                .style_signal("--domcom-input-accent", map_ref! {
+                    let invalid = invalid(),
+                    let colour = self.colour.signal() => {
+                        if *invalid { Colour::Danger.var() } else { colour.var() }
+                    }
+                })
+                .style_signal("padding", self.size.signal().map(|s| format!("0 {}", s.mult(0.75).var())))
+                .style_signal("min-height", self.size.signal().map(|s| s.mult(2.25).var()))
+                .style_signal("border-radius", self.radius.signal().map(|r| r.mult(0.45).var()))
+                .class_signal("opacity-60", self.disabled.signal())
+                .class_signal("cursor-not-allowed", self.disabled.signal())
+                .child(section(self.left_section.take()).unwrap_or_else(|| html!("span", {})))
+                .child(html!("input" => HtmlInputElement, {
+                    .class("flex-1")
+                    .class("min-w-0")
+                    .class("bg-transparent")
+                    .class("outline-none")
+                    .class("disabled:cursor-not-allowed")
+                    .attr("id", &id)
+                    .attr("type", if self.input_type.is_empty() { "text" } else { self.input_type })
+                    .attr("aria-describedby", &format!("{} {}", description_id, error_id))
+                    .attr_signal("aria-invalid", invalid().map(|invalid| invalid.then_some("true")))
+                    .attr_signal("placeholder", self.placeholder.signal().map(|p| (!p.is_empty()).then_some(p)))
+                    .prop_signal("disabled", self.disabled.signal())
+                    .prop_signal("readOnly", self.readonly.signal())
+                    .prop_signal("value", self.value.signal_cloned())
+                    .with_node!(input => {
+                        .event({
+                            let value = self.value.clone();
+                            move |_: events::Input| value.set_neq(input.value())
+                        })
+                        .apply(|dom| match self.touched.take() {
+                            Some(touched) => dom.event(move |_: events::Blur| touched.set_neq(true)),
+                            None => dom,
+                        })
+                    })
+                }))
+                .child(section(self.right_section.take()).unwrap_or_else(|| html!("span", {})))
+            }))
+            .child(html!("p", {
+                .class("text-[0.85em]")
+                .attr("id", &error_id)
+                .attr("aria-live", "polite")
+                .style("color", &Colour::Danger.var())
+                .visible_signal(invalid())
+                .text_signal(self.error.signal())
+            }))
+        })
+    }
+}
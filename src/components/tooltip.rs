@@ -1,11 +1,12 @@
 use std::collections::HashMap;
 
-use dominator::{html, Dom};
+use dominator::{apply_methods, html, Dom, DomBuilder};
 use factoryizer::Factory;
+use web_sys::HtmlElement;
 
 use crate::helpers::safety::none_dom;
 
-use super::{ty::Reactive, Component};
+use super::{ty::Props, Component};
 
 #[derive(Default, Clone, Hash, PartialEq, Eq)]
 pub enum Position {
@@ -19,10 +20,10 @@ pub enum Position {
 lazy_static::lazy_static! {
     static ref POS_STYLES: HashMap<Position, Vec<String>> = {
         let mut m = HashMap::new();
-        m.insert(Position::Top, vec!["bottom-[120%]", "origin-bottom", "after:rotate-180", "after:bottom-[-0.85rem]", "after:left-[50%]", "after:translate-x-[-7.5px]"].iter().map(|s| s.to_string()).collect::<Vec<String>>());
-        m.insert(Position::Bottom, vec!["-bottom-[120%]", "origin-top", "after:rotate-0", "after:top-[-0.85rem]", "after:left-[50%]", "after:translate-x-[-7.5px]"].iter().map(|s| s.to_string()).collect::<Vec<String>>());
-        m.insert(Position::Left, vec!["right-[120%]", "origin-right", "after:rotate-90", "after:right-[-0.9rem]"].iter().map(|s| s.to_string()).collect::<Vec<String>>());
-        m.insert(Position::Right, vec!["-right-[120%]", "origin-left", "after:-rotate-90", "after:left-[-0.9rem]"].iter().map(|s| s.to_string()).collect::<Vec<String>>());
+        m.insert(Position::Top, ["bottom-[120%]", "origin-bottom", "after:rotate-180", "after:bottom-[-0.85rem]", "after:left-[50%]", "after:translate-x-[-7.5px]"].iter().map(|s| s.to_string()).collect::<Vec<String>>());
+        m.insert(Position::Bottom, ["-bottom-[120%]", "origin-top", "after:rotate-0", "after:top-[-0.85rem]", "after:left-[50%]", "after:translate-x-[-7.5px]"].iter().map(|s| s.to_string()).collect::<Vec<String>>());
+        m.insert(Position::Left, ["right-[120%]", "origin-right", "after:rotate-90", "after:right-[-0.9rem]"].iter().map(|s| s.to_string()).collect::<Vec<String>>());
+        m.insert(Position::Right, ["-right-[120%]", "origin-left", "after:-rotate-90", "after:left-[-0.9rem]"].iter().map(|s| s.to_string()).collect::<Vec<String>>());
         m
     };
 }
@@ -36,7 +37,7 @@ pub struct Tooltip {
     #[skip]
     text: String,
     #[skip]
-    styles: Vec<(String, Reactive<String>)>,
+    props: Props,
 }
 
 impl Tooltip {
@@ -51,25 +52,17 @@ impl Tooltip {
 }
 
 impl Component for Tooltip {
-    fn style(&mut self, style: (String, Reactive<String>)) -> &mut Self {
-        self.styles.push(style);
-        self
+    fn props(&mut self) -> &mut Props {
+        &mut self.props
     }
 
-    fn dom(&mut self) -> Dom {
-        html!("div", {
+    fn render(&mut self) -> DomBuilder<HtmlElement> {
+        apply_methods!(DomBuilder::<HtmlElement>::new_html("div"), {
             .class("relative")
             .class("grid")
             .class("place-items-center")
             .class("group")
-            .child(self.child.take().unwrap_or_else(|| none_dom()))
-            .apply(|mut d| {
-                for (k, v) in self.styles.iter() {
-                    d = v.apply_style(k.to_string(), d);
-                }
-
-                d
-            })
+            .child(self.child.take().unwrap_or_else(none_dom))
             .child(html!("span", {
 
                 // Layout
@@ -1,20 +1,74 @@
-use dominator::{Dom, DomBuilder};
-use futures_signals::signal::Mutable;
-use std::hash::Hash;
+use dominator::{traits::StaticEvent, Dom, DomBuilder};
+use futures_signals::signal::{always, Broadcaster, LocalBoxSignal, Mutable, Signal, SignalExt};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display},
+    hash::Hash,
+    ops::{Add, Div, Mul, Sub},
+    rc::Rc,
+    str::FromStr,
+};
 use web_sys::HtmlElement;
 
-use crate::helpers::theme::THEME;
+use crate::helpers::{
+    colours::{best_text_on, best_text_on_translucent, css_opacity, Rgba, BLACK, TRANSLUCENT, TRANSPARENT, WHITE},
+    media::insert_rule,
+    safety::rand_id,
+    theme::{watch_global_theme, Theme, ThemeContext, DEFAULT_PALETTE, THEME},
+};
 
 #[derive(Default, Clone, Debug, Hash, PartialEq, Eq)]
 pub enum Colour {
+    // Semantic
+    Primary,
+    Secondary,
+    Success,
+    Warning,
+    Danger,
+    Info,
+
+    // Named
     Grey,
     #[default]
     Blue,
     Coral,
     Pink,
-    Hex(&'static str),
+    Green,
+    Red,
+    Yellow,
+    Teal,
+    Violet,
+    Orange,
+
+    Hex(Rgba),
+}
+
+impl From<Rgba> for Colour {
+    fn from(value: Rgba) -> Self {
+        Colour::Hex(value)
+    }
 }
 
+const COLOUR_TOKENS: [(Colour, &str); 16] = [
+    (Colour::Primary, "primary"),
+    (Colour::Secondary, "secondary"),
+    (Colour::Success, "success"),
+    (Colour::Warning, "warning"),
+    (Colour::Danger, "danger"),
+    (Colour::Info, "info"),
+    (Colour::Grey, "grey"),
+    (Colour::Blue, "blue"),
+    (Colour::Coral, "coral"),
+    (Colour::Pink, "pink"),
+    (Colour::Green, "green"),
+    (Colour::Red, "red"),
+    (Colour::Yellow, "yellow"),
+    (Colour::Teal, "teal"),
+    (Colour::Violet, "violet"),
+    (Colour::Orange, "orange"),
+];
+
 #[derive(Default, Clone, Debug)]
 pub enum TextColour {
     Light,
@@ -24,8 +78,11 @@ pub enum TextColour {
     Hex(&'static str),
 }
 
+/// A CSS length. `Xs` to `Xl` are theme sizes, see [`Theme::sizing`](crate::helpers::theme::Theme),
+/// and lengths compose with `+`, `-`, `*`, `/`, [`Length::min`] and [`Length::max`].
 #[derive(Default, Clone, Debug)]
-pub enum RemSizing {
+pub enum Length {
+    /// Zero
     None,
     Xs,
     Sm,
@@ -33,149 +90,861 @@ pub enum RemSizing {
     Md,
     Lg,
     Xl,
+    Px(f32),
     Rem(f32),
+    Em(f32),
+    Percent(f32),
+    Vw(f32),
+    Vh(f32),
+    Auto,
+    Calc(Box<Length>, CalcOp, Box<Length>),
+    Scaled(Box<Length>, f32),
+    Min(Vec<Length>),
+    Max(Vec<Length>),
+}
+
+#[deprecated(note = "use `Length`, which also covers other units")]
+pub type RemSizing = Length;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CalcOp {
+    Add,
+    Sub,
+}
+
+/// Bits of `value` with `-0.0` folded into `0.0` and every NaN into one,
+/// so `Length`'s equality and hash agree
+fn float_key(value: f32) -> u32 {
+    match value {
+        _ if value == 0.0 => 0,
+        _ if value.is_nan() => f32::NAN.to_bits(),
+        _ => value.to_bits(),
+    }
 }
 
-impl PartialEq for RemSizing {
+impl PartialEq for Length {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (RemSizing::None, RemSizing::None) => true,
-            (RemSizing::Xs, RemSizing::Xs) => true,
-            (RemSizing::Sm, RemSizing::Sm) => true,
-            (RemSizing::Md, RemSizing::Md) => true,
-            (RemSizing::Lg, RemSizing::Lg) => true,
-            (RemSizing::Xl, RemSizing::Xl) => true,
-            (RemSizing::Rem(a), RemSizing::Rem(b)) => a == b,
-            _ => false,
+            (Length::Px(a), Length::Px(b))
+            | (Length::Rem(a), Length::Rem(b))
+            | (Length::Em(a), Length::Em(b))
+            | (Length::Percent(a), Length::Percent(b))
+            | (Length::Vw(a), Length::Vw(b))
+            | (Length::Vh(a), Length::Vh(b)) => float_key(*a) == float_key(*b),
+            (Length::Calc(a, op, b), Length::Calc(other_a, other_op, other_b)) => {
+                a == other_a && op == other_op && b == other_b
+            }
+            (Length::Scaled(length, factor), Length::Scaled(other_length, other_factor)) => {
+                length == other_length && float_key(*factor) == float_key(*other_factor)
+            }
+            (Length::Min(lengths), Length::Min(others)) | (Length::Max(lengths), Length::Max(others)) => {
+                lengths == others
+            }
+            // Unit variants, or a mismatch
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
         }
     }
 }
 
-impl Eq for RemSizing {}
-impl Hash for RemSizing {
+impl Eq for Length {}
+impl Hash for Length {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Length::Px(value)
+            | Length::Rem(value)
+            | Length::Em(value)
+            | Length::Percent(value)
+            | Length::Vw(value)
+            | Length::Vh(value) => float_key(*value).hash(state),
+            Length::Calc(a, op, b) => {
+                a.hash(state);
+                op.hash(state);
+                b.hash(state);
+            }
+            Length::Scaled(length, factor) => {
+                length.hash(state);
+                float_key(*factor).hash(state);
+            }
+            Length::Min(lengths) | Length::Max(lengths) => lengths.hash(state),
+            _ => {}
+        }
+    }
+}
+
+impl Add for Length {
+    type Output = Length;
+
+    fn add(self, rhs: Length) -> Length {
+        Length::Calc(Box::new(self), CalcOp::Add, Box::new(rhs))
+    }
+}
+
+impl Sub for Length {
+    type Output = Length;
+
+    fn sub(self, rhs: Length) -> Length {
+        Length::Calc(Box::new(self), CalcOp::Sub, Box::new(rhs))
+    }
+}
+
+impl Mul<f32> for Length {
+    type Output = Length;
+
+    fn mul(self, rhs: f32) -> Length {
         match self {
-            RemSizing::None => 0.hash(state),
-            RemSizing::Xs => 1.hash(state),
-            RemSizing::Sm => 2.hash(state),
-            RemSizing::Md => 3.hash(state),
-            RemSizing::Lg => 4.hash(state),
-            RemSizing::Xl => 5.hash(state),
-            RemSizing::Rem(rem) => rem.to_bits().hash(state),
+            Length::None | Length::Auto => self,
+            Length::Px(value) => Length::Px(value * rhs),
+            Length::Rem(value) => Length::Rem(value * rhs),
+            Length::Em(value) => Length::Em(value * rhs),
+            Length::Percent(value) => Length::Percent(value * rhs),
+            Length::Vw(value) => Length::Vw(value * rhs),
+            Length::Vh(value) => Length::Vh(value * rhs),
+            Length::Scaled(length, factor) => Length::Scaled(length, factor * rhs),
+            _ => Length::Scaled(Box::new(self), rhs),
         }
     }
 }
 
+impl Div<f32> for Length {
+    type Output = Length;
+
+    fn div(self, rhs: f32) -> Length {
+        self * (1.0 / rhs)
+    }
+}
+
+type Listener = Rc<dyn Fn(DomBuilder<HtmlElement>) -> DomBuilder<HtmlElement>>;
+
+/// Styles, classes, attributes and events set through [`Component`],
+/// applied to a component's root element by [`Component::dom`]
+#[derive(Default, Clone)]
+pub struct Props {
+    styles: Vec<(String, Reactive<String>)>,
+    classes: Vec<String>,
+    attrs: Vec<(String, Reactive<String>)>,
+    events: Vec<Listener>,
+}
+
+impl Props {
+    pub fn apply(&self, mut d: DomBuilder<HtmlElement>) -> DomBuilder<HtmlElement> {
+        for (name, value) in self.styles.iter() {
+            d = value.apply_style(name.to_string(), d);
+        }
+        for class in self.classes.iter() {
+            d = d.class(class);
+        }
+        for (name, value) in self.attrs.iter() {
+            d = value.apply_attr(name.to_string(), d);
+        }
+        for event in self.events.iter() {
+            d = event(d);
+        }
+        d
+    }
+}
+
 pub trait Component {
     // Helper
-    fn style(&mut self, style: (String, Reactive<String>)) -> &mut Self;
+    fn props(&mut self) -> &mut Props;
 
     // Constructing functions
-    fn mt(&mut self, s: RemSizing) -> &mut Self {
-        self.style(("margin-top".to_string(), s.to_string().into()))
+    fn style(&mut self, style: (String, Reactive<String>)) -> &mut Self {
+        self.props().styles.push(style);
+        self
+    }
+    fn class(&mut self, class: &str) -> &mut Self {
+        self.props().classes.push(class.to_string());
+        self
+    }
+    fn attr(&mut self, name: &str, value: impl Into<Reactive<String>>) -> &mut Self {
+        self.props().attrs.push((name.to_string(), value.into()));
+        self
+    }
+    fn id(&mut self, id: &str) -> &mut Self {
+        self.attr("id", id.to_string())
     }
-    fn mb(&mut self, s: RemSizing) -> &mut Self {
-        self.style(("margin-bottom".to_string(), s.to_string().into()))
+    /// Sets `aria-<name>`, e.g. `.aria("label", "Close")`
+    fn aria(&mut self, name: &str, value: impl Into<Reactive<String>>) -> &mut Self {
+        self.attr(&format!("aria-{}", name), value)
     }
-    fn ml(&mut self, s: RemSizing) -> &mut Self {
-        self.style(("margin-left".to_string(), s.to_string().into()))
+    fn on<E: StaticEvent>(&mut self, handler: impl Fn(E) + 'static) -> &mut Self {
+        let handler = Rc::new(handler);
+        self.props().events.push(Rc::new(move |d| {
+            let handler = handler.clone();
+            d.event(move |e: E| handler(e))
+        }));
+        self
     }
-    fn mr(&mut self, s: RemSizing) -> &mut Self {
-        self.style(("margin-right".to_string(), s.to_string().into()))
+
+    /// Inline style, or a class with media queries when `value` varies by breakpoint
+    fn responsive_style(&mut self, name: &'static str, value: Responsive<String>) -> &mut Self {
+        if value.is_static() {
+            self.style((name.to_string(), value.base().clone().into()))
+        } else {
+            let class = value.class("", |value| vec![(name, value.clone())]);
+            self.class(&class)
+        }
+    }
+
+    fn mt(&mut self, s: impl Into<Responsive<Length>>) -> &mut Self {
+        self.responsive_style("margin-top", s.into().map(|s| s.var()))
+    }
+    fn mb(&mut self, s: impl Into<Responsive<Length>>) -> &mut Self {
+        self.responsive_style("margin-bottom", s.into().map(|s| s.var()))
+    }
+    fn ml(&mut self, s: impl Into<Responsive<Length>>) -> &mut Self {
+        self.responsive_style("margin-left", s.into().map(|s| s.var()))
+    }
+    fn mr(&mut self, s: impl Into<Responsive<Length>>) -> &mut Self {
+        self.responsive_style("margin-right", s.into().map(|s| s.var()))
+    }
+    fn p(&mut self, s: impl Into<Responsive<Length>>) -> &mut Self {
+        self.responsive_style("padding", s.into().map(|s| s.var()))
+    }
+    fn pt(&mut self, s: impl Into<Responsive<Length>>) -> &mut Self {
+        self.responsive_style("padding-top", s.into().map(|s| s.var()))
+    }
+    fn pb(&mut self, s: impl Into<Responsive<Length>>) -> &mut Self {
+        self.responsive_style("padding-bottom", s.into().map(|s| s.var()))
+    }
+    fn pl(&mut self, s: impl Into<Responsive<Length>>) -> &mut Self {
+        self.responsive_style("padding-left", s.into().map(|s| s.var()))
+    }
+    fn pr(&mut self, s: impl Into<Responsive<Length>>) -> &mut Self {
+        self.responsive_style("padding-right", s.into().map(|s| s.var()))
+    }
+    fn w(&mut self, s: impl Into<Responsive<Length>>) -> &mut Self {
+        self.responsive_style("width", s.into().map(|s| s.var()))
+    }
+    fn h(&mut self, s: impl Into<Responsive<Length>>) -> &mut Self {
+        self.responsive_style("height", s.into().map(|s| s.var()))
     }
 
     // Computing functions
-    fn dom(&mut self) -> Dom;
+    /// Builds the root element, without the shared [`Props`]
+    fn render(&mut self) -> DomBuilder<HtmlElement>;
+
+    fn dom(&mut self) -> Dom {
+        let props = self.props().clone();
+        props.apply(self.render()).into_dom()
+    }
 }
 
-impl ToString for Colour {
-    fn to_string(&self) -> String {
-        let colours = THEME.get_cloned().colours.unwrap();
+impl Colour {
+    /// Resolves against a palette, see [`ThemeContext::palette_signal`].
+    /// Colours missing from the palette or that don't parse fall back to
+    /// their alias, then to the default theme.
+    pub fn resolve(&self, palette: &HashMap<Colour, String>) -> String {
+        let valid = |colour: &Colour| palette.get(colour).filter(|value| Rgba::parse(value).is_ok());
         match self {
-            Colour::Hex(hex) => hex.to_string(),
-            _ => colours.get(self).unwrap().to_string(),
+            Colour::Hex(value) => value.to_css(),
+            _ => valid(self)
+                .or_else(|| self.alias().and_then(|alias| valid(&alias)))
+                .or_else(|| DEFAULT_PALETTE.get(self))
+                .cloned()
+                .unwrap_or_else(|| TRANSPARENT.to_string()),
         }
     }
+
+    /// Named colour a semantic colour falls back to
+    pub fn alias(&self) -> Option<Colour> {
+        match self {
+            Colour::Primary => Some(Colour::Blue),
+            Colour::Secondary => Some(Colour::Grey),
+            Colour::Success => Some(Colour::Green),
+            Colour::Warning => Some(Colour::Yellow),
+            Colour::Danger => Some(Colour::Red),
+            Colour::Info => Some(Colour::Teal),
+            _ => None,
+        }
+    }
+
+    /// Every theme colour, i.e. everything but `Hex`
+    pub fn all() -> impl Iterator<Item = Colour> {
+        COLOUR_TOKENS.into_iter().map(|(colour, _)| colour)
+    }
+
+    /// The resolved colour, updated whenever the global theme or scheme changes
+    #[deprecated(note = "reads the global theme, ignoring Providers; use `var()` or `signal_in(&ThemeContext::closest(..))`")]
+    pub fn signal(&self) -> impl Signal<Item = String> {
+        self.signal_in(&ThemeContext::global())
+    }
+
+    /// The resolved colour within a Provider, see [`ThemeContext::closest`]
+    pub fn signal_in(&self, context: &ThemeContext) -> impl Signal<Item = String> {
+        let colour = self.clone();
+        context.palette_signal().map(move |p| colour.resolve(&p))
+    }
+
+    /// Design-token name of a theme colour, `None` for `Hex`
+    pub fn token(&self) -> Option<&'static str> {
+        COLOUR_TOKENS
+            .iter()
+            .find(|(colour, _)| colour == self)
+            .map(|(_, token)| *token)
+    }
+
+    pub fn from_token(token: &str) -> Option<Self> {
+        COLOUR_TOKENS
+            .iter()
+            .find(|(_, name)| *name == token)
+            .map(|(colour, _)| colour.clone())
+    }
+
+    /// Name of the CSS variable a Provider writes this colour to
+    pub fn variable(&self) -> Option<String> {
+        self.token().map(|token| format!("--domcom-colour-{}", token))
+    }
+
+    /// Resolves against the default theme, the fallback for a missing variable.
+    /// Outside of any Provider the variables come from the global theme on `:root`.
+    fn resolve_fallback(&self) -> String {
+        watch_global_theme();
+        self.resolve(&DEFAULT_PALETTE)
+    }
+
+    /// CSS value referencing the closest Provider's colour, or the global theme's outside of one
+    pub fn var(&self) -> String {
+        match self.variable() {
+            Some(name) => format!("var({}, {})", name, self.resolve_fallback()),
+            None => self.resolve_fallback(),
+        }
+    }
+
+    /// CSS value for a step (50 to 900) of this colour's shade scale
+    pub fn shade(&self, step: u16) -> String {
+        let value = self.resolve_fallback();
+        let fallback = Rgba::parse(&value)
+            .map(|colour| colour.shade(step, false).to_css())
+            .unwrap_or(value);
+        match self.variable() {
+            Some(name) => format!("var({}-{}, {})", name, step, fallback),
+            None => fallback,
+        }
+    }
+
+    /// CSS value for readable text on top of this colour,
+    /// black when the colour doesn't parse
+    pub fn contrast_var(&self) -> String {
+        let fallback = Rgba::parse(&self.resolve_fallback())
+            .map(|colour| best_text_on(colour, WHITE, &[BLACK, WHITE]))
+            .unwrap_or(BLACK)
+            .to_css();
+        match self.variable() {
+            Some(name) => format!("var({}-contrast, {})", name, fallback),
+            None => fallback,
+        }
+    }
+
+    /// CSS value for this colour at [`TRANSLUCENT`] opacity
+    pub fn translucent(&self) -> String {
+        css_opacity(&self.var(), TRANSLUCENT)
+    }
+
+    /// CSS value for readable text on top of [`translucent`](Self::translucent),
+    /// which accounts for the page showing through
+    pub fn translucent_contrast_var(&self) -> String {
+        let fallback = Rgba::parse(&self.resolve_fallback())
+            .map(|colour| best_text_on_translucent(colour, WHITE))
+            .unwrap_or(BLACK)
+            .to_css();
+        match self.variable() {
+            Some(name) => format!("var({}-contrast-75, {})", name, fallback),
+            None => fallback,
+        }
+    }
+}
+
+/// Formats as [`Colour::var`], so the value follows the closest Provider
+impl Display for Colour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.var())
+    }
 }
 
-impl ToString for TextColour {
-    fn to_string(&self) -> String {
+impl TextColour {
+    pub fn resolve(&self, palette: &HashMap<Colour, String>) -> String {
         match self {
             TextColour::Light => "#ffffff".to_string(),
             TextColour::Dark => "#000000".to_string(),
-            TextColour::Accent => Colour::Blue.to_string(),
+            TextColour::Accent => Colour::Blue.resolve(palette),
             TextColour::Hex(hex) => hex.to_string(),
         }
     }
-}
 
-impl ToString for RemSizing {
-    fn to_string(&self) -> String {
-        let sizing = THEME.get_cloned().sizing.unwrap();
+    #[deprecated(note = "reads the global theme, ignoring Providers; use `var()` or `signal_in(&ThemeContext::closest(..))`")]
+    pub fn signal(&self) -> impl Signal<Item = String> {
+        self.signal_in(&ThemeContext::global())
+    }
+
+    pub fn signal_in(&self, context: &ThemeContext) -> impl Signal<Item = String> {
+        let colour = self.clone();
+        context.palette_signal().map(move |p| colour.resolve(&p))
+    }
+
+    pub fn var(&self) -> String {
         match self {
-            RemSizing::None => "{}rem".to_string(),
-            RemSizing::Rem(rem) => format!("{}rem", rem),
-            _ => format!("{}rem", sizing.get(self).unwrap()),
+            TextColour::Accent => Colour::Blue.var(),
+            _ => self.resolve(&HashMap::new()),
         }
     }
 }
 
-impl RemSizing {
+/// Formats as [`TextColour::var`], so the value follows the closest Provider
+impl Display for TextColour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.var())
+    }
+}
+
+/// Formats as [`Length::var`], so theme sizes follow the closest Provider
+impl Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.var())
+    }
+}
+
+impl Length {
+    pub fn min(lengths: impl IntoIterator<Item = Length>) -> Self {
+        Length::Min(lengths.into_iter().collect())
+    }
+
+    pub fn max(lengths: impl IntoIterator<Item = Length>) -> Self {
+        Length::Max(lengths.into_iter().collect())
+    }
+
+    /// CSS value with theme sizes looked up in `sizing`,
+    /// falling back to the default theme
+    pub fn resolve(&self, sizing: &HashMap<Length, f32>) -> String {
+        self.css(&|size| match size {
+            Length::None => "0".to_string(),
+            _ => {
+                let rem = sizing.get(size).copied().or(size.default_rem()).unwrap_or(0.0);
+                format!("{}rem", rem)
+            }
+        })
+    }
+
+    /// CSS value with `size` writing theme sizes and `None`
+    fn css(&self, size: &impl Fn(&Length) -> String) -> String {
+        let list = |lengths: &Vec<Length>| {
+            lengths
+                .iter()
+                .map(|length| length.css(size))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        match self {
+            Length::None | Length::Xs | Length::Sm | Length::Md | Length::Lg | Length::Xl => size(self),
+            Length::Px(value) => format!("{}px", value),
+            Length::Rem(value) => format!("{}rem", value),
+            Length::Em(value) => format!("{}em", value),
+            Length::Percent(value) => format!("{}%", value),
+            Length::Vw(value) => format!("{}vw", value),
+            Length::Vh(value) => format!("{}vh", value),
+            Length::Auto => "auto".to_string(),
+            Length::Calc(a, op, b) => format!(
+                "calc({} {} {})",
+                a.css(size),
+                match op {
+                    CalcOp::Add => "+",
+                    CalcOp::Sub => "-",
+                },
+                b.css(size)
+            ),
+            Length::Scaled(length, factor) => format!("calc({} * {})", length.css(size), factor),
+            Length::Min(lengths) => format!("min({})", list(lengths)),
+            Length::Max(lengths) => format!("max({})", list(lengths)),
+        }
+    }
+
+    /// The resolved length, updated whenever the global theme changes
+    #[deprecated(note = "reads the global theme, ignoring Providers; use `var()` or `signal_in(&ThemeContext::closest(..))`")]
+    pub fn signal(&self) -> impl Signal<Item = String> {
+        self.signal_in(&ThemeContext::global())
+    }
+
+    /// The resolved length within a Provider, see [`ThemeContext::closest`]
+    pub fn signal_in(&self, context: &ThemeContext) -> impl Signal<Item = String> {
+        let length = self.clone();
+        context
+            .theme
+            .signal_ref(move |theme| length.resolve(&theme.sizing.clone().unwrap_or_default()))
+    }
+
+    /// Design-token name of a theme size, `None` for any other length
+    pub fn token(&self) -> Option<&'static str> {
+        match self {
+            Length::Xs => Some("xs"),
+            Length::Sm => Some("sm"),
+            Length::Md => Some("md"),
+            Length::Lg => Some("lg"),
+            Length::Xl => Some("xl"),
+            _ => None,
+        }
+    }
+
+    pub fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "none" => Some(Length::None),
+            "xs" => Some(Length::Xs),
+            "sm" => Some(Length::Sm),
+            "md" => Some(Length::Md),
+            "lg" => Some(Length::Lg),
+            "xl" => Some(Length::Xl),
+            _ => None,
+        }
+    }
+
+    /// Size of a theme token in the default theme
+    pub fn default_rem(&self) -> Option<f32> {
+        match self {
+            Length::Xs => Some(0.5),
+            Length::Sm => Some(0.75),
+            Length::Md => Some(1.0),
+            Length::Lg => Some(1.5),
+            Length::Xl => Some(2.0),
+            _ => None,
+        }
+    }
+
+    /// Name of the CSS variable a Provider writes this size to
+    pub fn variable(&self) -> Option<String> {
+        self.token().map(|token| format!("--domcom-size-{}", token))
+    }
+
+    /// CSS value where theme sizes reference the closest Provider,
+    /// or the global theme outside of one
+    pub fn var(&self) -> String {
+        watch_global_theme();
+        let sizing = HashMap::new();
+        self.css(&|size| match size.variable() {
+            Some(name) => format!("var({}, {})", name, size.resolve(&sizing)),
+            None => size.resolve(&sizing),
+        })
+    }
+
     pub fn mult(&self, value: f32) -> Self {
+        self.clone() * value
+    }
+
+    /// CSS with theme sizes and `None` written as their tokens, e.g. `calc(md + 10px)`,
+    /// which [`FromStr`] reads back
+    pub fn token_css(&self) -> String {
+        self.css(&|size| match size {
+            Length::None => "none".to_string(),
+            _ => size.token().unwrap_or_default().to_string(),
+        })
+    }
+}
+
+/// The arguments of a call to the CSS function `name`
+fn css_function<'a>(value: &'a str, name: &str) -> Option<&'a str> {
+    value.strip_prefix(name)?.strip_prefix('(')?.strip_suffix(')')
+}
+
+/// Byte index of the first `pattern` outside of brackets
+fn find_top_level(value: &str, pattern: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ if depth == 0 && value[i..].starts_with(pattern) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+impl FromStr for Length {
+    type Err = String;
+
+    /// Parses a theme token, `auto`, a number with a unit, bare numbers are rem,
+    /// or the `calc()`, `min()` and `max()` expressions [`Length::token_css`] writes
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let invalid = || format!("Invalid length: {}", value);
+        if let Some(length) = Length::from_token(value) {
+            return Ok(length);
+        }
+        if value == "auto" {
+            return Ok(Length::Auto);
+        }
+
+        if let Some(arguments) = css_function(value, "calc") {
+            // CSS needs spaces around `+` and `-`, which tells them apart from signs
+            let (index, operator) = [" + ", " - ", " * "]
+                .into_iter()
+                .filter_map(|operator| Some((find_top_level(arguments, operator)?, operator)))
+                .min()
+                .ok_or_else(invalid)?;
+            let (a, b) = (&arguments[..index], &arguments[index + operator.len()..]);
+            return match operator {
+                " * " => Ok(Length::Scaled(Box::new(a.parse()?), b.trim().parse().map_err(|_| invalid())?)),
+                " + " => Ok(Length::Calc(Box::new(a.parse()?), CalcOp::Add, Box::new(b.parse()?))),
+                _ => Ok(Length::Calc(Box::new(a.parse()?), CalcOp::Sub, Box::new(b.parse()?))),
+            };
+        }
+
+        type Function = fn(Vec<Length>) -> Length;
+        let functions: [(&str, Function); 2] = [("min", Length::Min), ("max", Length::Max)];
+        for (name, function) in functions {
+            if let Some(mut arguments) = css_function(value, name) {
+                let mut lengths = Vec::new();
+                while let Some(index) = find_top_level(arguments, ",") {
+                    lengths.push(arguments[..index].parse()?);
+                    arguments = &arguments[index + 1..];
+                }
+                lengths.push(arguments.parse()?);
+                return Ok(function(lengths));
+            }
+        }
+
+        type Unit = fn(f32) -> Length;
+        let units: [(&str, Unit); 6] = [
+            ("px", Length::Px),
+            ("rem", Length::Rem),
+            ("em", Length::Em),
+            ("%", Length::Percent),
+            ("vw", Length::Vw),
+            ("vh", Length::Vh),
+        ];
+        let (number, unit): (&str, Unit) = units
+            .into_iter()
+            .find_map(|(suffix, unit)| Some((value.strip_suffix(suffix)?, unit)))
+            .unwrap_or((value, Length::Rem));
+
+        number
+            .trim()
+            .parse::<f32>()
+            .map(unit)
+            .map_err(|_| invalid())
+    }
+}
+
+/// Minimum viewport widths, set by [`Theme::breakpoints`](crate::helpers::theme::Theme)
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Breakpoint {
+    Sm,
+    Md,
+    Lg,
+    Xl,
+}
+
+impl Breakpoint {
+    pub fn all() -> [Breakpoint; 4] {
+        [Breakpoint::Sm, Breakpoint::Md, Breakpoint::Lg, Breakpoint::Xl]
+    }
+
+    pub fn token(&self) -> &'static str {
         match self {
-            RemSizing::None => RemSizing::None,
-            RemSizing::Xs => RemSizing::Rem(0.5 * value),
-            RemSizing::Sm => RemSizing::Rem(0.75 * value),
-            RemSizing::Md => RemSizing::Rem(value),
-            RemSizing::Lg => RemSizing::Rem(1.5 * value),
-            RemSizing::Xl => RemSizing::Rem(2.0 * value),
-            RemSizing::Rem(rem) => RemSizing::Rem(rem * value),
+            Breakpoint::Sm => "sm",
+            Breakpoint::Md => "md",
+            Breakpoint::Lg => "lg",
+            Breakpoint::Xl => "xl",
+        }
+    }
+
+    pub fn from_token(token: &str) -> Option<Self> {
+        Breakpoint::all().into_iter().find(|b| b.token() == token)
+    }
+
+    /// Width in rem from the global theme. Media queries can't read
+    /// CSS variables, so nested Providers don't change this.
+    pub fn min_width(&self) -> f32 {
+        self.width_in(&THEME.lock_ref())
+    }
+
+    pub fn width_in(&self, theme: &Theme) -> f32 {
+        theme
+            .breakpoints
+            .as_ref()
+            .and_then(|breakpoints| breakpoints.get(self).copied())
+            .unwrap_or_else(|| self.default_width())
+    }
+
+    pub fn default_width(&self) -> f32 {
+        match self {
+            Breakpoint::Sm => 40.0,
+            Breakpoint::Md => 48.0,
+            Breakpoint::Lg => 64.0,
+            Breakpoint::Xl => 80.0,
+        }
+    }
+}
+
+/// A value that changes from a breakpoint upwards, e.g.
+/// `Responsive::new(FlexDirection::Column).md(FlexDirection::Row)`
+#[derive(Clone, Debug, Default)]
+pub struct Responsive<T> {
+    base: T,
+    breakpoints: BTreeMap<Breakpoint, T>,
+}
+
+impl<T> From<T> for Responsive<T> {
+    fn from(value: T) -> Self {
+        Responsive::new(value)
+    }
+}
+
+impl<T> Responsive<T> {
+    pub fn new(base: T) -> Self {
+        Self {
+            base,
+            breakpoints: BTreeMap::new(),
+        }
+    }
+
+    pub fn at(mut self, breakpoint: Breakpoint, value: T) -> Self {
+        self.breakpoints.insert(breakpoint, value);
+        self
+    }
+    pub fn sm(self, value: T) -> Self {
+        self.at(Breakpoint::Sm, value)
+    }
+    pub fn md(self, value: T) -> Self {
+        self.at(Breakpoint::Md, value)
+    }
+    pub fn lg(self, value: T) -> Self {
+        self.at(Breakpoint::Lg, value)
+    }
+    pub fn xl(self, value: T) -> Self {
+        self.at(Breakpoint::Xl, value)
+    }
+
+    /// Value below the smallest breakpoint
+    pub fn base(&self) -> &T {
+        &self.base
+    }
+
+    /// Whether the value is the same at every width
+    pub fn is_static(&self) -> bool {
+        self.breakpoints.is_empty()
+    }
+
+    /// The value in effect at `breakpoint`, i.e. the closest one at or below it
+    pub fn get(&self, breakpoint: Option<Breakpoint>) -> &T {
+        breakpoint
+            .and_then(|breakpoint| self.breakpoints.range(..=breakpoint).next_back())
+            .map(|(_, value)| value)
+            .unwrap_or(&self.base)
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Responsive<U> {
+        Responsive {
+            base: f(&self.base),
+            breakpoints: self
+                .breakpoints
+                .iter()
+                .map(|(breakpoint, value)| (*breakpoint, f(value)))
+                .collect(),
         }
     }
+
+    /// A class applying `styles` for each value, wrapped in media queries
+    /// for the breakpoints. `selector` is appended to the class, e.g. `" > * + *"`.
+    /// Identical rules share one class, so re-rendering doesn't grow the stylesheet.
+    pub fn class(&self, selector: &str, styles: impl Fn(&T) -> Vec<(&'static str, String)>) -> String {
+        let mut breakpoints = self.breakpoints.iter().collect::<Vec<_>>();
+        breakpoints.sort_by(|(a, _), (b, _)| a.min_width().total_cmp(&b.min_width()));
+        let rules = std::iter::once((None, styles(&self.base)))
+            .chain(breakpoints.into_iter().map(|(breakpoint, value)| (Some(breakpoint.min_width()), styles(value))))
+            .collect::<Vec<_>>();
+
+        let key = format!("{}{:?}", selector, rules);
+        RESPONSIVE_CLASSES.with(|classes| {
+            classes
+                .borrow_mut()
+                .entry(key)
+                .or_insert_with(|| {
+                    let class = format!("domcom-{}", rand_id());
+                    let rule = format!(".{}{}", class, selector);
+                    for (min_width, styles) in rules.iter() {
+                        insert_rule(*min_width, &rule, styles);
+                    }
+                    class
+                })
+                .clone()
+        })
+    }
+}
+
+thread_local! {
+    /// Classes made by [`Responsive::class`], keyed by selector and rules
+    static RESPONSIVE_CLASSES: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
 }
 
+/// A component prop that is either fixed, backed by a [`Mutable`]
+/// or driven by any other signal
 #[derive(Clone)]
 pub enum Reactive<T> {
     Static(T),
     Dynamic(Mutable<T>),
+    Signal(Broadcaster<LocalBoxSignal<'static, T>>),
 }
 
-impl Default for Reactive<String> {
+impl<T: Default> Default for Reactive<T> {
     fn default() -> Self {
-        Reactive::Static("".to_string())
+        Reactive::Static(T::default())
     }
 }
 
-impl Default for Reactive<&'static str> {
-    fn default() -> Self {
-        Reactive::Static("")
+impl<T> From<T> for Reactive<T> {
+    fn from(value: T) -> Self {
+        Reactive::Static(value)
     }
 }
 
-// Style/Class implimentations
-impl From<String> for Reactive<String> {
-    fn from(value: String) -> Self {
-        Reactive::Static(value)
+impl<T> From<Mutable<T>> for Reactive<T> {
+    fn from(value: Mutable<T>) -> Self {
+        Reactive::Dynamic(value)
     }
 }
 
-impl From<Mutable<String>> for Reactive<String> {
-    fn from(value: Mutable<String>) -> Self {
-        Reactive::Dynamic(value)
+impl<T: Clone + 'static> Reactive<T> {
+    /// Wraps a signal, e.g. a mapped or combined one, so it can be
+    /// shared by every place the prop is used
+    pub fn from_signal(signal: impl Signal<Item = T> + 'static) -> Self {
+        Reactive::Signal(Broadcaster::new(signal.boxed_local()))
+    }
+
+    pub fn signal(&self) -> LocalBoxSignal<'static, T> {
+        match self {
+            Reactive::Static(value) => always(value.clone()).boxed_local(),
+            Reactive::Dynamic(value) => value.signal_cloned().boxed_local(),
+            Reactive::Signal(value) => value.signal_cloned().boxed_local(),
+        }
+    }
+
+    pub fn map<U: Clone + 'static>(&self, f: impl Fn(T) -> U + 'static) -> Reactive<U> {
+        match self {
+            Reactive::Static(value) => Reactive::Static(f(value.clone())),
+            _ => Reactive::from_signal(self.signal().map(f)),
+        }
     }
 }
 
-impl Reactive<String> {
+// Style/Class implimentations
+impl<T: Display + Clone + 'static> Reactive<T> {
     pub fn apply_style(&self, name: String, e: DomBuilder<HtmlElement>) -> DomBuilder<HtmlElement> {
         match self {
-            Reactive::Static(value) => e.style(name, value),
-            Reactive::Dynamic(value) => e.style_signal(name, value.signal_cloned()),
+            Reactive::Static(value) => e.style(name, value.to_string()),
+            _ => e.style_signal(name, self.signal().map(|value| value.to_string())),
+        }
+    }
+    pub fn apply_attr(&self, name: String, e: DomBuilder<HtmlElement>) -> DomBuilder<HtmlElement> {
+        match self {
+            Reactive::Static(value) => e.attr(&name, &value.to_string()),
+            _ => e.attr_signal(name, self.signal().map(|value| Some(value.to_string()))),
         }
     }
     pub fn apply_text(&self, e: DomBuilder<HtmlElement>) -> DomBuilder<HtmlElement> {
         match self {
-            Reactive::Static(value) => e.text(value),
-            Reactive::Dynamic(value) => e.text_signal(value.signal_cloned()),
+            Reactive::Static(value) => e.text(&value.to_string()),
+            _ => e.text_signal(self.signal().map(|value| value.to_string())),
         }
     }
 }
@@ -186,3 +955,100 @@ impl From<&'static str> for Reactive<String> {
         Reactive::Static(value.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{hash_map::DefaultHasher, HashMap},
+        hash::{Hash, Hasher},
+    };
+
+    use super::{Breakpoint, Colour, Length, Responsive};
+    use crate::helpers::theme::DEFAULT_PALETTE;
+
+    fn hash(length: &Length) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        length.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn equal_lengths_hash_equally() {
+        let pairs = [
+            (Length::Px(0.0), Length::Px(-0.0)),
+            (Length::Rem(f32::NAN), Length::Rem(-f32::NAN)),
+            (Length::Md * 0.0, Length::Md * -0.0),
+            (Length::Md + Length::Px(-0.0), Length::Md + Length::Px(0.0)),
+            (Length::min([Length::Vw(f32::NAN)]), Length::min([Length::Vw(f32::NAN)])),
+        ];
+        for (a, b) in pairs {
+            assert_eq!(a, b);
+            assert_eq!(hash(&a), hash(&b), "{:?} and {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn different_lengths_are_unequal() {
+        assert_ne!(Length::Px(1.0), Length::Rem(1.0));
+        assert_ne!(Length::Px(1.0), Length::Px(2.0));
+        assert_ne!(Length::Md, Length::Lg);
+        assert_ne!(Length::Md + Length::Px(1.0), Length::Md - Length::Px(1.0));
+        assert_eq!(Length::Auto, Length::Auto);
+    }
+
+    fn palette(pairs: &[(Colour, &str)]) -> HashMap<Colour, String> {
+        pairs.iter().map(|(colour, value)| (colour.clone(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn semantic_colours_fall_back_to_their_alias() {
+        let palette = palette(&[(Colour::Blue, "#0000ff"), (Colour::Red, "#ff0000"), (Colour::Success, "#00ff00")]);
+        assert_eq!(Colour::Primary.resolve(&palette), "#0000ff");
+        assert_eq!(Colour::Danger.resolve(&palette), "#ff0000");
+        assert_eq!(Colour::Success.resolve(&palette), "#00ff00");
+    }
+
+    #[test]
+    fn missing_colours_fall_back_to_the_default_palette() {
+        let default = |colour: &Colour| DEFAULT_PALETTE[colour].clone();
+        assert_eq!(Colour::Pink.resolve(&HashMap::new()), default(&Colour::Pink));
+        // Neither Primary nor its alias Blue is set
+        assert_eq!(Colour::Primary.resolve(&HashMap::new()), default(&Colour::Primary));
+
+        let invalid = palette(&[(Colour::Pink, "not a colour"), (Colour::Danger, "#12"), (Colour::Red, "#ff0000")]);
+        assert_eq!(Colour::Pink.resolve(&invalid), default(&Colour::Pink));
+        assert_eq!(Colour::Danger.resolve(&invalid), "#ff0000");
+    }
+
+    #[test]
+    fn breakpoints_widen_in_order() {
+        let all = Breakpoint::all();
+        for pair in all.windows(2) {
+            assert!(pair[0] < pair[1]);
+            assert!(pair[0].default_width() < pair[1].default_width());
+        }
+        for breakpoint in all {
+            assert_eq!(Breakpoint::from_token(breakpoint.token()), Some(breakpoint));
+        }
+    }
+
+    #[test]
+    fn responsive_values_cascade_upwards() {
+        let value = Responsive::new(1).md(2).xl(4);
+        assert_eq!(*value.get(None), 1);
+        assert_eq!(*value.get(Some(Breakpoint::Sm)), 1);
+        assert_eq!(*value.get(Some(Breakpoint::Md)), 2);
+        assert_eq!(*value.get(Some(Breakpoint::Lg)), 2);
+        assert_eq!(*value.get(Some(Breakpoint::Xl)), 4);
+        assert!(Responsive::new(1).is_static());
+        assert!(!value.is_static());
+    }
+
+    #[test]
+    fn responsive_map_keeps_breakpoints() {
+        let value = Responsive::new(1).sm(2).lg(3).map(|value| value * 10);
+        assert_eq!(*value.base(), 10);
+        let at = Breakpoint::all().map(|breakpoint| *value.get(Some(breakpoint)));
+        assert_eq!(at, [20, 20, 30, 30]);
+    }
+}
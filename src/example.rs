@@ -3,16 +3,29 @@ use crate::components::{
     tabs::Tab,
     text::TextVariant,
     tooltip::Tooltip,
-    ty::{Colour, RemSizing},
+    flex::FlexDirection,
+    ty::{Colour, Length, Reactive, Responsive},
     *,
 };
 use crate::components::{
-    shell::SidebarItem,
+    shell::{SidebarGroup, SidebarItem, SidebarLink},
+    form::FormModel,
+    radio_group::Orientation,
     table::{numerical_sort, Table, TableValues},
 };
-use dominator::{class, html, with_node, Dom};
-use crate::helpers::Provider;
-use std::rc::Rc;
+use crate::{DomcomForm, DomcomTable};
+use dominator::{class, clone, html, with_node, Dom};
+use crate::helpers::{
+    colours::Deficiency,
+    media::{breakpoint_signal, prefers_reduced_motion_signal, window_size_signal},
+    theme::ColourScheme,
+    Provider,
+};
+use futures_signals::{
+    map_ref,
+    signal::{Mutable, SignalExt},
+};
+use std::{cell::RefCell, rc::Rc};
 use tabler_dominator::icon;
 use wasm_bindgen::prelude::*;
 
@@ -53,12 +66,12 @@ pub fn display_case(element: Dom, label: &str, tooltip: Option<&str>) -> Dom {
                     }
                 )
                 .apply(|mut d| {
-                    if tooltip.is_some() {
+                    if let Some(tooltip) = tooltip {
                         d = d.child(
                             Tooltip::new()
                                 .child(icon!("info-circle"))
-                                .text(tooltip.unwrap())
-                                .ml(RemSizing::Md)
+                                .text(tooltip)
+                                .ml(Length::Md)
                                 .dom()
                         )
                     }
@@ -70,6 +83,90 @@ pub fn display_case(element: Dom, label: &str, tooltip: Option<&str>) -> Dom {
     })
 }
 
+#[derive(DomcomTable, DomcomForm)]
+struct Station {
+    name: String,
+    #[domcom(label = "Elevation (m)")]
+    elevation: u32,
+    #[domcom(cell = staffed)]
+    staffed: bool,
+    #[domcom(hidden)]
+    #[allow(dead_code)]
+    id: u64,
+}
+
+impl Station {
+    fn all() -> Vec<Station> {
+        vec![
+            Station { name: "Mount Kosciuszko".to_string(), elevation: 2228, staffed: false, id: 1 },
+            Station { name: "Thredbo".to_string(), elevation: 1380, staffed: true, id: 2 },
+            Station { name: "Perisher".to_string(), elevation: 1720, staffed: true, id: 3 },
+        ]
+    }
+}
+
+fn staffed(staffed: &bool) -> String {
+    if *staffed { "Yes" } else { "No" }.to_string()
+}
+
+fn station_form() -> Dom {
+    let form = Form::new();
+    let station = Rc::new(RefCell::new(Station::all().remove(1)));
+    let saved = Mutable::new(String::new());
+
+    form.on_submit({
+        let station = station.clone();
+        let saved = saved.clone();
+        move |values| {
+            let mut station = station.borrow_mut();
+            station.apply(&values);
+            saved.set(format!("Saved {} at {}m", station.name, station.elevation));
+            async {}
+        }
+    });
+
+    let mut children = form.model(&*station.borrow());
+    children.push(Button::new().text("Save").attr("type", "submit").dom());
+    children.push(html!("code", { .text_signal(saved.signal_cloned()) }));
+    form.dom(children)
+}
+
+fn signup_form() -> Dom {
+    let form = Form::new();
+    let name = form.text("name", "").required("Enter your name");
+    let age = form.number("age", None).validate_with(|age| match age.parse::<f64>() {
+        Ok(age) if age < 18.0 => Err("You must be 18 or over".to_string()),
+        _ => Ok(()),
+    });
+    let plan = form.select("plan", "free");
+    let terms = form.checkbox("terms", false).required("Accept the terms to continue");
+    let submitted = Mutable::new(String::new());
+
+    form.on_submit({
+        let submitted = submitted.clone();
+        move |values| {
+            submitted.set(format!("{:?}", values));
+            async {}
+        }
+    });
+
+    form.dom(vec![
+        TextInput::new().label("Name").field(&name).dom(),
+        TextInput::new().label("Age").field(&age).dom(),
+        SegmentedControl::new().option(("Free", "free")).option(("Pro", "pro")).field(&plan).dom(),
+        Checkbox::new().label("I accept the terms").field(&terms).dom(),
+        Flex::new()
+            .space_x(Length::Sm)
+            .child(Button::new().text("Submit").attr("type", "submit").dom())
+            .child(Button::new().text("Reset").variant(ButtonVariant::Subtle).attr("type", "button").on_click({
+                let form = form.clone();
+                move || form.reset()
+            }).dom())
+            .dom(),
+        html!("code", { .text_signal(submitted.signal_cloned()) }),
+    ])
+}
+
 pub fn row(label: &str, elements: Vec<Dom>) -> Dom {
     html!("div", {
         .style("margin-top", "2rem")
@@ -97,7 +194,7 @@ pub fn row(label: &str, elements: Vec<Dom>) -> Dom {
     })
 }
 
-pub fn display() -> Dom {
+pub fn display(scheme: Mutable<ColourScheme>, deficiency: Mutable<Option<Deficiency>>) -> Dom {
     let table_data = vec![
         TableValues::new()
             .title("Max Temp".to_string())
@@ -115,8 +212,18 @@ pub fn display() -> Dom {
             .clone(),
     ];
 
+    let email = Mutable::new(String::new());
+
     Shell::new()
     .title("Components — Example page")
+            .brand(Some(html!("span", { .class("w-8").class("h-8").child(icon!("components")) })))
+            .actions(Some(html!("a", {
+                .attr("href", "https://github.com/tascord/domcom")
+                .attr("aria-label", "Source on GitHub")
+                .class("w-6")
+                .class("h-6")
+                .child(icon!("brand-github"))
+            })))
             .sidebar(vec![
                 SidebarItem::Title("Components"),
                 SidebarItem::Item("Tables", "#table"),
@@ -124,18 +231,98 @@ pub fn display() -> Dom {
                 SidebarItem::Item("Buttons", "#button"),
                 SidebarItem::Item("Text", "#text"),
                 SidebarItem::Item("Utils", "#util"),
+                SidebarItem::Spacer,
+                SidebarItem::Group(SidebarGroup::new("More").icon(icon!("folder")).items(vec![
+                    SidebarItem::Link(SidebarLink::new("Notifications", "#util").icon(icon!("bell")).badge(Mutable::new(3))),
+                    SidebarItem::Link(SidebarLink::new("Settings (coming soon)", "#settings").icon(icon!("settings")).disabled(true)),
+                ])),
             ])
             .child(Some(
                 html!("div", {
+                    .child(row("Theme", vec![
+                        display_case(
+                            Flex::new()
+                                .child(Button::new().variant(ButtonVariant::Light).text("Light").on_click(clone!(scheme => move || scheme.set(ColourScheme::Light))).dom())
+                                .child(Button::new().variant(ButtonVariant::Light).text("Dark").on_click(clone!(scheme => move || scheme.set(ColourScheme::Dark))).dom())
+                                .child(Button::new().variant(ButtonVariant::Light).text("System").on_click(clone!(scheme => move || scheme.set(ColourScheme::System))).dom())
+                                .space_x(Length::Sm)
+                                .space_y(Length::None)
+                                .dom(),
+                            "Colour Scheme", Some("System follows the browser's prefers-color-scheme setting.")
+                        ),
+                        display_case(
+                            Flex::new()
+                                .child(Button::new().variant(ButtonVariant::Light).text("Normal").on_click(clone!(deficiency => move || deficiency.set(None))).dom())
+                                .child(Button::new().variant(ButtonVariant::Light).text("Protanopia").on_click(clone!(deficiency => move || deficiency.set(Some(Deficiency::Protanopia)))).dom())
+                                .child(Button::new().variant(ButtonVariant::Light).text("Deuteranopia").on_click(clone!(deficiency => move || deficiency.set(Some(Deficiency::Deuteranopia)))).dom())
+                                .child(Button::new().variant(ButtonVariant::Light).text("Tritanopia").on_click(clone!(deficiency => move || deficiency.set(Some(Deficiency::Tritanopia)))).dom())
+                                .child(Button::new().variant(ButtonVariant::Light).text("Achromatopsia").on_click(clone!(deficiency => move || deficiency.set(Some(Deficiency::Achromatopsia)))).dom())
+                                .space_x(Length::Sm)
+                                .space_y(Length::None)
+                                .dom(),
+                            "Colour Vision", Some("Previews the whole gallery as seen with a colour-vision deficiency.")
+                        ),
+                        display_case(
+                            Provider::new()
+                                .colour_scheme(Mutable::new(ColourScheme::Dark))
+                                .child(html!("div", {
+                                    .class("p-4")
+                                    .class("bg-neutral-900")
+                                    .child(Button::new().colour(Colour::Pink).text("Always dark").dom())
+                                }))
+                                .dom(),
+                            "Nested Provider", Some("A nested Provider only themes its own descendants.")
+                        ),
+                    ]))
                     .child(row("Inputs", vec![
                         display_case(SegmentedControl::new().option(("Read Only", "ro")).option(("Read/Write", "rw")).default_value("rw").dom(), "Segmented Control (Default)", None),
                         display_case(SegmentedControl::new().option(("Read Only", "ro")).option(("Read/Write", "rw")).default_value("rw").colour(Colour::Pink).dom(), "Segmented Control (Pink)", None),
                         display_case(SegmentedControl::new().option(("Read Only", "ro")).option(("Read/Write", "rw")).default_value("rw").colour(Colour::Coral).dom(), "Segmented Control (Coral)", None),
-                        display_case(SegmentedControl::new().option(("Read Only", "ro")).option(("Read/Write", "rw")).default_value("rw").colour(Colour::Grey).dom(), "Segmented Control (Grey)", None)
+                        display_case(SegmentedControl::new().option(("Read Only", "ro")).option(("Read/Write", "rw")).default_value("rw").colour(Colour::Grey).dom(), "Segmented Control (Grey)", None),
+                        display_case(TextInput::new().label("Name").description("As it appears on your badge").placeholder("Ada Lovelace").dom(), "Text Input (Default)", None),
+                        display_case(TextInput::new().placeholder("Search").left_section(icon!("search")).colour(Colour::Pink).radius(Length::Xl).dom(), "Text Input (Icon)", None),
+                        display_case(
+                            TextInput::new()
+                                .label("Email")
+                                .value(email.clone())
+                                .error(Reactive::from_signal(email.signal_ref(|email| {
+                                    if email.contains('@') { String::new() } else { "Enter an email address".to_string() }
+                                })))
+                                .dom(),
+                            "Text Input (Validated)", None
+                        ),
+                        display_case(TextInput::new().label("Disabled").value(Mutable::new("Can't touch this".to_string())).disabled(true).dom(), "Text Input (Disabled)", None),
+                        display_case(Checkbox::new().label("Email me updates").description("At most once a week").dom(), "Checkbox", None),
+                        display_case(Checkbox::new().label("Select all").indeterminate(Mutable::new(true)).colour(Colour::Pink).dom(), "Checkbox (Indeterminate)", None),
+                        display_case(Switch::new().label("Dark mode").checked(Mutable::new(true)).colour(Colour::Green).size(Length::Lg).dom(), "Switch", None),
+                        display_case(
+                            RadioGroup::new()
+                                .label("Delivery")
+                                .option(("Standard", "standard"))
+                                .option(("Express", "express"))
+                                .option(("Pick up", "pickup"))
+                                .value(Mutable::new("standard".to_string()))
+                                .dom(),
+                            "Radio Group", None
+                        ),
+                        display_case(
+                            RadioGroup::new()
+                                .option(("Small", "sm"))
+                                .option(("Medium", "md"))
+                                .option(("Large", "lg"))
+                                .orientation(Responsive::new(Orientation::Vertical).md(Orientation::Horizontal))
+                                .colour(Colour::Coral)
+                                .dom(),
+                            "Radio Group (Horizontal from md)", None
+                        )
+                    ]))
+                    .child(row("Form", vec![
+                        display_case(signup_form(), "Form", Some("Errors show after a field is left or a submit is attempted")),
+                        display_case(station_form(), "Form (Derived)", None),
                     ]))
                     .child(row("Table", vec![
                         display_case(
-                            Table::new().data(table_data.clone().into()).direction(TableDirection::Column).dom(),
+                            Table::new().data(table_data.clone().into()).direction(TableDirection::Column).sync_url("sort").dom(),
                             "Table (Default)", None
                         ),
                         display_case(
@@ -150,12 +337,13 @@ pub fn display() -> Dom {
                             Table::new().data(table_data.clone().into()).direction(TableDirection::Column).colour(Colour::Grey).dom(),
                             "Table (Grey)", None
                         ),
+                        display_case(Table::new().rows(&Station::all()).direction(TableDirection::Column).dom(), "Table (Derived)", None),
                     ]))
                     .child(row("Tabs", vec![
                         display_case(Tabs::new().placement(tabs::TabPlacement::Bottom).tabs(vec![
                             Tab::new("Source Code", "source", html!("pre", { .text("<rust>") })),
                             Tab::new("Generated Html", "generated", html!("pre", { .text("<html>") }))
-                        ]).dom(), "Tabs (Bottom)", None),
+                        ]).sync_url("tab").dom(), "Tabs (Bottom)", None),
                         display_case(Tabs::new().colour(Colour::Pink).placement(tabs::TabPlacement::Top).tabs(vec![
                             Tab::new("Source Code", "source", html!("pre", { .text("<rust>") })),
                             Tab::new("Generated Html", "generated", html!("pre", { .text("<html>") }))
@@ -168,12 +356,27 @@ pub fn display() -> Dom {
                             Tab::new("Source Code", "source", html!("pre", { .text("<rust>") })),
                             Tab::new("Generated Html", "generated", html!("pre", { .text("<html>") }))
                         ]).dom(), "Tabs (Right, Grey)", None),
+                        display_case(Tabs::new().colour(Colour::Teal).placement(Responsive::new(tabs::TabPlacement::Top).md(tabs::TabPlacement::Left)).tabs(vec![
+                            Tab::new("Source Code", "source", html!("pre", { .text("<rust>") })),
+                            Tab::new("Generated Html", "generated", html!("pre", { .text("<html>") }))
+                        ]).dom(), "Tabs (Top, Left from md)", Some("Placement changes at the theme's md breakpoint.")),
                     ]))
                     .child(row("Button", vec![
                         display_case(Button::new().text("Hello, world!").dom(), "Button (Solid, Blue)", None),
                         display_case(Button::new().text("Hello, world!").variant(ButtonVariant::Light).colour(Colour::Pink).dom(), "Button (Light, Pink)", None),
                         display_case(Button::new().text("Hello, world!").variant(ButtonVariant::Outline).colour(Colour::Coral).dom(), "Button (Outline, Coral)", None),
                         display_case(Button::new().text("Hello, world!").variant(ButtonVariant::Subtle).colour(Colour::Grey).dom(), "Button (Subtle, Grey)", None),
+                        display_case(Button::new().text("Saved").colour(Colour::Success).dom(), "Button (Solid, Success)", None),
+                        display_case(Button::new().text("Delete").variant(ButtonVariant::Light).colour(Colour::Danger).dom(), "Button (Light, Danger)", None),
+                        display_case({
+                            let armed = Mutable::new(false);
+                            Button::new()
+                                .text("Click to toggle")
+                                .colour(Reactive::from_signal(armed.signal().map(|armed| if armed { Colour::Danger } else { Colour::Success })))
+                                .variant(Reactive::from_signal(armed.signal().map(|armed| if armed { ButtonVariant::Filled } else { ButtonVariant::Outline })))
+                                .on_click(clone!(armed => move || armed.set(!armed.get())))
+                                .dom()
+                        }, "Button (Reactive props)", Some("Colour and variant follow application state without rebuilding the button.")),
                     ]))
                     .child(row("Text", vec![
                         display_case(Text::new().text("Hello, world!").variant(TextVariant::Subscript).dom(), "Text (Subcript)", None),
@@ -190,11 +393,39 @@ pub fn display() -> Dom {
                             Flex::new()
                                 .child(Button::new().colour(Colour::Coral).variant(ButtonVariant::Light).text("Hello,").dom())
                                 .child(Button::new().colour(Colour::Coral).variant(ButtonVariant::Light).text("world!").dom())
-                                .space_x(RemSizing::Md)
-                                .space_y(RemSizing::None)
+                                .space_x(Length::Md)
+                                .space_y(Length::None)
                                 .dom(),
                             "Flex", Some("Flex is a component that allows you to easily space elements in a row or column.")
                         ),
+                        display_case(
+                            Flex::new()
+                                .child(Button::new().colour(Colour::Violet).variant(ButtonVariant::Light).text("Stacked on phones,").dom())
+                                .child(Button::new().colour(Colour::Violet).variant(ButtonVariant::Light).text("side by side from md").dom())
+                                .direction(Responsive::new(FlexDirection::Column).md(FlexDirection::Row))
+                                .space_x(Responsive::new(Length::None).md(Length::Md))
+                                .space_y(Responsive::new(Length::Md).md(Length::None))
+                                .dom(),
+                            "Flex (Responsive)", Some("Direction and spacing follow the theme's breakpoints.")
+                        ),
+                        display_case(
+                            html!("code", {
+                                .text_signal(map_ref! {
+                                    let (width, height) = window_size_signal(),
+                                    let breakpoint = breakpoint_signal(),
+                                    let reduced_motion = prefers_reduced_motion_signal() => {
+                                        format!(
+                                            "{}x{}, breakpoint: {}, reduced motion: {}",
+                                            width,
+                                            height,
+                                            breakpoint.map(|b| b.token()).unwrap_or("none"),
+                                            reduced_motion
+                                        )
+                                    }
+                                })
+                            }),
+                            "Viewport signals", Some("Shared listeners behind window_size_signal, breakpoint_signal and prefers_reduced_motion_signal.")
+                        ),
                         display_case(
                             Tooltip::new()
                                 .child(Button::new().colour(Colour::Blue).variant(ButtonVariant::Light).text("Hover me!").dom())
@@ -209,8 +440,12 @@ pub fn display() -> Dom {
 pub struct App {}
 impl App {
     pub fn render(self: Rc<Self>) -> Dom {
+        let scheme = Mutable::new(ColourScheme::System);
+        let deficiency = Mutable::new(None);
         let mut provider = Provider::new();
-        provider.child(display());
+        provider.colour_scheme(scheme.clone());
+        provider.simulate(deficiency.clone());
+        provider.child(display(scheme, deficiency));
         provider.dom()
     }
 }
@@ -1,40 +1,689 @@
-use colors_transform::{Color, Rgb};
-use hex_color::HexColor;
+use std::{error::Error, fmt, str::FromStr};
+
+use colors_transform::{Color, Hsl, Rgb};
 pub static TRANSPARENT: &str = "#00000000";
 
-/// Every function in colours.rs returns a String, which is a HEX.
-/// This means that every function can be chained into each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidColour(pub String);
 
-fn parse(hex_colour: String) -> HexColor {
-    HexColor::parse(&hex_colour).expect(format!("Invalid hex colour: {}", hex_colour).as_str())
+impl fmt::Display for InvalidColour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid colour: {}", self.0)
+    }
 }
 
-pub fn luma(hex_colour: String) -> f32 {
-    let rgb = parse(hex_colour);
+impl Error for InvalidColour {}
 
-    // SMPTE C, Rec. 709 weightings
-    (0.2126 * rgb.r as f32) + (0.7152 * rgb.g as f32) + (0.0722 * rgb.b as f32)
+/// A parsed colour. Every operation returns a new value so they can be chained:
+/// `Rgba::parse("#5576B9")?.darken(0.25).with_alpha(0.5).to_css()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
 }
 
-pub fn bw_on_bg(background_hex: String) -> String {
-    if luma(background_hex.clone()) > 165.0 {
-        "#000000".to_string()
+impl Rgba {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    /// Parses `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()`, `rgba()`,
+    /// `hsl()`, `hsla()` and CSS named colours
+    pub fn parse(value: &str) -> Result<Self, InvalidColour> {
+        let invalid = || InvalidColour(value.to_string());
+        let trimmed = value.trim().to_ascii_lowercase();
+
+        if let Some(hex) = trimmed.strip_prefix('#') {
+            return parse_hex(hex).ok_or_else(invalid);
+        }
+
+        if let Some((function, arguments)) = trimmed
+            .strip_suffix(')')
+            .and_then(|rest| rest.split_once('('))
+        {
+            return parse_function(function.trim(), arguments).ok_or_else(invalid);
+        }
+
+        NAMED_COLOURS
+            .binary_search_by_key(&trimmed.as_str(), |(name, _)| name)
+            .map(|i| {
+                let [_, r, g, b] = NAMED_COLOURS[i].1.to_be_bytes();
+                Rgba::new(r, g, b)
+            })
+            .or(match trimmed.as_str() {
+                "transparent" => Ok(Rgba { r: 0, g: 0, b: 0, a: 0 }),
+                _ => Err(invalid()),
+            })
+    }
+
+    pub fn alpha(&self) -> f32 {
+        self.a as f32 / 255.0
+    }
+
+    pub fn with_alpha(self, alpha: f32) -> Self {
+        Self {
+            a: (alpha.clamp(0.0, 1.0) * 255.0).round() as u8,
+            ..self
+        }
+    }
+
+    pub fn darken(self, amount: f32) -> Self {
+        self.lighten(-amount)
+    }
+
+    /// `amount` of 1.0 moves the HSL lightness by 50 points
+    pub fn lighten(self, amount: f32) -> Self {
+        self.map_hsl(|hsl| hsl.lighten(amount * 50.0))
+    }
+
+    /// `amount` of 1.0 moves the HSL saturation by 50 points
+    pub fn saturate(self, amount: f32) -> Self {
+        self.map_hsl(|hsl| hsl.saturate(amount * 50.0))
+    }
+
+    /// Mixes in `amount` (0 to 1) of `other`, alpha included
+    pub fn mix(self, other: Rgba, amount: f32) -> Self {
+        let amount = amount.clamp(0.0, 1.0);
+        let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+
+        Self {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+            a: channel(self.a, other.a),
+        }
+    }
+
+    /// Step 500 is the colour itself. Lower steps move its HSL lightness towards
+    /// the page background and higher steps away from it, so on a `dark` scheme
+    /// 50 is still the most subtle tint and 900 the strongest.
+    pub fn shade(self, step: u16, dark: bool) -> Self {
+        let (background, foreground) = if dark { (10.0, 95.0) } else { (97.0, 12.0) };
+        let t = (step.min(1000) as f32 - 500.0) / 500.0;
+
+        self.map_hsl(|hsl| {
+            let lightness = hsl.get_lightness();
+            hsl.set_lightness(if t < 0.0 {
+                lightness + (background - lightness) * -t
+            } else {
+                lightness + (foreground - lightness) * t
+            })
+        })
+    }
+
+    /// WCAG 2.x relative luminance, alpha is ignored
+    pub fn relative_luminance(&self) -> f32 {
+        0.2126 * to_linear(self.r) + 0.7152 * to_linear(self.g) + 0.0722 * to_linear(self.b)
+    }
+
+    /// How this colour appears with the given colour-vision deficiency,
+    /// see [`Deficiency::matrix`]
+    pub fn simulate(self, deficiency: Deficiency) -> Self {
+        let matrix = deficiency.matrix();
+        let rgb = [to_linear(self.r), to_linear(self.g), to_linear(self.b)];
+        let channel = |row: [f32; 3]| from_linear(row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]);
+
+        Self {
+            r: channel(matrix[0]),
+            g: channel(matrix[1]),
+            b: channel(matrix[2]),
+            a: self.a,
+        }
+    }
+
+    /// Composites this colour over `base`, the result is opaque
+    pub fn over(self, base: Rgba) -> Self {
+        Rgba { a: 255, ..base }.mix(Rgba { a: 255, ..self }, self.alpha())
+    }
+
+    pub fn luma(&self) -> f32 {
+        // SMPTE C, Rec. 709 weightings
+        (0.2126 * self.r as f32) + (0.7152 * self.g as f32) + (0.0722 * self.b as f32)
+    }
+
+    /// `#rrggbb`, or `#rrggbbaa` when not fully opaque
+    pub fn to_css(&self) -> String {
+        match self.a {
+            255 => format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b),
+            a => format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, a),
+        }
+    }
+
+    fn map_hsl(self, f: impl FnOnce(Hsl) -> Hsl) -> Self {
+        let rgb = f(Rgb::from(self.r as f32, self.g as f32, self.b as f32).to_hsl()).to_rgb();
+        Self {
+            r: rgb.get_red().round() as u8,
+            g: rgb.get_green().round() as u8,
+            b: rgb.get_blue().round() as u8,
+            a: self.a,
+        }
+    }
+}
+
+impl FromStr for Rgba {
+    type Err = InvalidColour;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Rgba::parse(value)
+    }
+}
+
+impl fmt::Display for Rgba {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_css())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Deficiency {
+    /// No red cones
+    Protanopia,
+    /// No green cones
+    Deuteranopia,
+    /// No blue cones
+    Tritanopia,
+    /// No colour vision, only luminance
+    Achromatopsia,
+}
+
+impl Deficiency {
+    /// Linear RGB transform, from the Machado et al. (2009) full-severity
+    /// matrices and, for achromatopsia, the WCAG luminance weights
+    fn matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            Deficiency::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Deficiency::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Deficiency::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+            Deficiency::Achromatopsia => [[0.2126, 0.7152, 0.0722]; 3],
+        }
+    }
+}
+
+fn to_linear(channel: u8) -> f32 {
+    let c = channel as f32 / 255.0;
+    if c <= 0.03928 {
+        c / 12.92
     } else {
-        "#ffffff".to_string()
+        ((c + 0.055) / 1.055).powf(2.4)
     }
 }
 
-pub fn opacity(hex_color: String, opacity: f32) -> String {
-    let opacity_hex_str = format!("{:02x}", (opacity * 255.0) as u8);
-    if hex_color.len() == 7 {
-        format!("{}{}", &hex_color, opacity_hex_str)
+fn from_linear(value: f32) -> u8 {
+    let c = value.clamp(0.0, 1.0);
+    let c = if c <= 0.0031308 {
+        c * 12.92
     } else {
-        format!("{}{}", &hex_color[..7], opacity_hex_str)
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+
+    (c * 255.0).round() as u8
+}
+
+fn parse_hex(hex: &str) -> Option<Rgba> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let digits: Vec<u8> = match hex.len() {
+        3 | 4 => hex
+            .chars()
+            .map(|c| u8::from_str_radix(&c.to_string().repeat(2), 16).ok())
+            .collect::<Option<_>>()?,
+        6 | 8 => (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+
+    Some(Rgba {
+        r: digits[0],
+        g: digits[1],
+        b: digits[2],
+        a: digits.get(3).copied().unwrap_or(255),
+    })
+}
+
+/// Splits `1, 2, 3`, `1 2 3` and `1 2 3 / 0.5` into their components
+fn parse_function(function: &str, arguments: &str) -> Option<Rgba> {
+    let arguments = arguments.replace(['/', ','], " ");
+    let parts = arguments.split_whitespace().collect::<Vec<_>>();
+    if parts.len() != 3 && parts.len() != 4 {
+        return None;
     }
+
+    let number = |part: &str, percent_of: f32| -> Option<f32> {
+        match part.strip_suffix('%') {
+            Some(percent) => percent.parse::<f32>().ok().map(|p| p / 100.0 * percent_of),
+            None => part.parse::<f32>().ok(),
+        }
+        .filter(|number| number.is_finite())
+    };
+    // Only hsl's hue is an angle
+    let hue = |part: &str| -> Option<f32> {
+        part.strip_suffix("deg")
+            .unwrap_or(part)
+            .parse::<f32>()
+            .ok()
+            .filter(|hue| hue.is_finite())
+    };
+    let alpha = match parts.get(3) {
+        Some(alpha) => number(alpha, 1.0)?,
+        None => 1.0,
+    };
+
+    let colour = match function {
+        "rgb" | "rgba" => Rgba::new(
+            number(parts[0], 255.0)?.clamp(0.0, 255.0).round() as u8,
+            number(parts[1], 255.0)?.clamp(0.0, 255.0).round() as u8,
+            number(parts[2], 255.0)?.clamp(0.0, 255.0).round() as u8,
+        ),
+        "hsl" | "hsla" => {
+            let rgb = Hsl::from(
+                hue(parts[0])?,
+                number(parts[1], 100.0)?,
+                number(parts[2], 100.0)?,
+            )
+            .to_rgb();
+            Rgba::new(
+                rgb.get_red().round() as u8,
+                rgb.get_green().round() as u8,
+                rgb.get_blue().round() as u8,
+            )
+        }
+        _ => return None,
+    };
+
+    Some(colour.with_alpha(alpha))
+}
+
+pub const BLACK: Rgba = Rgba::new(0, 0, 0);
+pub const WHITE: Rgba = Rgba::new(255, 255, 255);
+
+/// Opacity of translucent highlights such as the Table header and selected Tab,
+/// Providers write a `-contrast-75` text colour for colours drawn at it
+pub const TRANSLUCENT: f32 = 0.75;
+
+/// Minimum WCAG AA contrast ratio for body text
+pub static WCAG_AA: f32 = 4.5;
+
+/// WCAG 2.x contrast ratio, from 1 to 21. Both colours are treated as opaque,
+/// composite translucent ones with [`Rgba::over`] first.
+pub fn contrast_ratio(a: Rgba, b: Rgba) -> f32 {
+    let (a, b) = (a.relative_luminance(), b.relative_luminance());
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// The candidate with the highest contrast on `background`,
+/// which is composited over `base` when translucent
+pub fn best_text_on(background: Rgba, base: Rgba, candidates: &[Rgba]) -> Rgba {
+    let background = background.over(base);
+    candidates
+        .iter()
+        .copied()
+        .max_by(|a, b| {
+            contrast_ratio(a.over(background), background)
+                .total_cmp(&contrast_ratio(b.over(background), background))
+        })
+        .unwrap_or(BLACK)
+}
+
+/// Black or white, whichever reads best on `colour` at [`TRANSLUCENT`] opacity over `page`
+pub fn best_text_on_translucent(colour: Rgba, page: Rgba) -> Rgba {
+    best_text_on(colour.with_alpha(colour.alpha() * TRANSLUCENT), page, &[BLACK, WHITE])
+}
+
+// The functions below take and return Strings so they can be chained into
+// each other. They panic on invalid colours, use Rgba to handle errors.
+
+fn parse(colour: String) -> Rgba {
+    Rgba::parse(&colour).unwrap_or_else(|e| panic!("{}", e))
+}
+
+pub fn luma(hex_colour: String) -> f32 {
+    parse(hex_colour).luma()
+}
+
+pub fn bw_on_bg(background_hex: String) -> String {
+    best_text_on(parse(background_hex), WHITE, &[BLACK, WHITE]).to_css()
+}
+
+pub fn opacity(hex_color: String, opacity: f32) -> String {
+    parse(hex_color).with_alpha(opacity).to_css()
 }
 
 pub fn darken(hex_colour: String, amount: f32) -> String {
-    let rgb = parse(hex_colour);
-    let hsl = Rgb::from(rgb.r as f32, rgb.g as f32, rgb.b as f32).to_hsl();
-    hsl.lighten(-(amount * 50.0)).to_rgb().to_css_hex_string()
+    parse(hex_colour).darken(amount).to_css()
+}
+
+pub static SHADE_STEPS: [u16; 10] = [50, 100, 200, 300, 400, 500, 600, 700, 800, 900];
+
+/// See [`Rgba::simulate`]
+pub fn simulate(hex_colour: String, deficiency: Deficiency) -> String {
+    parse(hex_colour).simulate(deficiency).to_css()
+}
+
+/// See [`Rgba::shade`]
+pub fn shade(hex_colour: String, step: u16, dark: bool) -> String {
+    parse(hex_colour).shade(step, dark).to_css()
+}
+
+// The css_* functions take any CSS colour value, including var() references,
+// and return a color-mix() expression the browser resolves.
+
+pub fn css_opacity(colour: &str, opacity: f32) -> String {
+    format!("color-mix(in srgb, {} {}%, transparent)", colour, (opacity * 100.0).round())
+}
+
+pub fn css_darken(colour: &str, amount: f32) -> String {
+    format!("color-mix(in srgb, {}, #000000 {}%)", colour, (amount * 100.0).round())
+}
+
+/// CSS named colours, sorted by name for binary searching
+static NAMED_COLOURS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::{best_text_on, best_text_on_translucent, contrast_ratio, Deficiency, Rgba, BLACK, WHITE};
+
+    fn rgba(r: u8, g: u8, b: u8, a: u8) -> Rgba {
+        Rgba { r, g, b, a }
+    }
+
+    #[test]
+    fn parses_valid_colours() {
+        let cases = [
+            ("#fff", rgba(255, 255, 255, 255)),
+            ("#0f08", rgba(0, 255, 0, 0x88)),
+            ("#5576B9", rgba(0x55, 0x76, 0xb9, 255)),
+            ("#11223344", rgba(0x11, 0x22, 0x33, 0x44)),
+            ("  RGB(255, 0, 0) ", rgba(255, 0, 0, 255)),
+            ("rgba(0, 0, 255, 0.5)", rgba(0, 0, 255, 128)),
+            ("rgb(0 128 0 / 50%)", rgba(0, 128, 0, 128)),
+            ("rgb(100%, 0%, 50%)", rgba(255, 0, 128, 255)),
+            ("rgb(300, -20, 0)", rgba(255, 0, 0, 255)),
+            ("hsl(0, 100%, 50%)", rgba(255, 0, 0, 255)),
+            ("hsl(120deg 100% 50%)", rgba(0, 255, 0, 255)),
+            ("hsla(240, 100%, 50%, 0.25)", rgba(0, 0, 255, 64)),
+            ("hsl(0, 0%, 100%)", rgba(255, 255, 255, 255)),
+            ("rebeccapurple", rgba(0x66, 0x33, 0x99, 255)),
+            ("White", rgba(255, 255, 255, 255)),
+            ("transparent", rgba(0, 0, 0, 0)),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(Rgba::parse(value), Ok(expected), "{}", value);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_colours() {
+        let cases = [
+            "",
+            "#",
+            "#12",
+            "#12345",
+            "#123456789",
+            "#ggg",
+            "rgb(1, 2)",
+            "rgb(1, 2, 3, 4, 5)",
+            "rgb(a, b, c)",
+            "rgb(10deg, 0, 0)",
+            "rgb(nan, 0, 0)",
+            "rgba(0, 0, 0, 1deg)",
+            "hsl(10%, 50%, 50%)",
+            "hsl(inf, 50%, 50%)",
+            "cmyk(1, 2, 3)",
+            "rgb(1, 2, 3",
+            "notacolour",
+        ];
+        for value in cases {
+            assert!(Rgba::parse(value).is_err(), "{} parsed", value);
+        }
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 0.01, "{} is not {}", actual, expected);
+    }
+
+    #[test]
+    fn contrast_ratios_match_wcag() {
+        assert_close(contrast_ratio(BLACK, WHITE), 21.0);
+        assert_close(contrast_ratio(WHITE, BLACK), 21.0);
+        assert_close(contrast_ratio(WHITE, WHITE), 1.0);
+        assert_close(contrast_ratio(rgba(0x55, 0x76, 0xb9, 255), rgba(0x55, 0x76, 0xb9, 255)), 1.0);
+        // The lightest grey that passes AA on white
+        assert_close(contrast_ratio(rgba(0x76, 0x76, 0x76, 255), WHITE), 4.54);
+    }
+
+    #[test]
+    fn best_text_flips_at_mid_grey() {
+        let grey = |value: u8| rgba(value, value, value, 255);
+        assert_eq!(best_text_on(grey(0x75), WHITE, &[BLACK, WHITE]), WHITE);
+        assert_eq!(best_text_on(grey(0x76), WHITE, &[BLACK, WHITE]), BLACK);
+        // A see-through background takes on the colour of the base below it
+        assert_eq!(best_text_on(BLACK.with_alpha(0.0), WHITE, &[BLACK, WHITE]), BLACK);
+        assert_eq!(best_text_on(WHITE.with_alpha(0.0), BLACK, &[BLACK, WHITE]), WHITE);
+    }
+
+    #[test]
+    fn translucent_text_follows_the_page() {
+        let grey = rgba(0x60, 0x60, 0x60, 255);
+        assert_eq!(best_text_on(grey, WHITE, &[BLACK, WHITE]), WHITE);
+        // At 75% the white page lightens it past mid grey, a black one darkens it
+        assert_eq!(best_text_on_translucent(grey, WHITE), BLACK);
+        assert_eq!(best_text_on_translucent(grey, BLACK), WHITE);
+    }
+
+    #[test]
+    fn shade_500_is_the_colour() {
+        let colours = [
+            rgba(0x55, 0x76, 0xb9, 255),
+            rgba(0xe0, 0x31, 0x31, 255),
+            rgba(0x1e, 0x1e, 0x1e, 128),
+            BLACK,
+            WHITE,
+        ];
+        for colour in colours {
+            assert_eq!(colour.shade(500, false), colour);
+            assert_eq!(colour.shade(500, true), colour);
+        }
+    }
+
+    const DEFICIENCIES: [Deficiency; 4] = [
+        Deficiency::Protanopia,
+        Deficiency::Deuteranopia,
+        Deficiency::Tritanopia,
+        Deficiency::Achromatopsia,
+    ];
+
+    #[test]
+    fn matrix_rows_keep_white_white() {
+        for deficiency in DEFICIENCIES {
+            for row in deficiency.matrix() {
+                assert!((row.iter().sum::<f32>() - 1.0).abs() < 1e-5, "{:?} row {:?}", deficiency, row);
+            }
+        }
+    }
+
+    #[test]
+    fn greys_are_unchanged() {
+        for deficiency in DEFICIENCIES {
+            for value in (0..=255).step_by(15) {
+                let grey = rgba(value, value, value, 200);
+                assert_eq!(grey.simulate(deficiency), grey, "{:?}", deficiency);
+            }
+        }
+    }
+
+    #[test]
+    fn achromatopsia_is_grey() {
+        for colour in [rgba(255, 0, 0, 255), rgba(0x55, 0x76, 0xb9, 255), rgba(0xee, 0x30, 0x75, 255)] {
+            let seen = colour.simulate(Deficiency::Achromatopsia);
+            assert!(seen.r == seen.g && seen.g == seen.b, "{:?}", seen);
+            assert!((seen.relative_luminance() - colour.relative_luminance()).abs() < 0.01);
+        }
+    }
 }
@@ -0,0 +1,138 @@
+//! Media queries, both as CSS rules `class!` can't express and as signals.
+//!
+//! Signals share one listener per window or query, created on first use.
+
+use std::{cell::RefCell, collections::HashMap};
+
+use futures_signals::{
+    map_ref,
+    signal::{Mutable, Signal, SignalExt},
+};
+use gloo::events::EventListener;
+use wasm_bindgen::JsCast;
+use web_sys::{CssStyleSheet, HtmlStyleElement};
+
+use crate::{components::ty::Breakpoint, helpers::theme::THEME};
+
+/// A shared value kept up to date by its listener
+struct Watched<T> {
+    value: Mutable<T>,
+    _listener: Option<EventListener>,
+}
+
+thread_local! {
+    static WINDOW_SIZE: Watched<(f64, f64)> = {
+        let value = Mutable::new(window_size());
+        let listener = web_sys::window().map(|window| {
+            EventListener::new(&window, "resize", {
+                let value = value.clone();
+                move |_| value.set(window_size())
+            })
+        });
+        Watched { value, _listener: listener }
+    };
+
+    static MEDIA_QUERIES: RefCell<HashMap<String, Watched<bool>>> = RefCell::new(HashMap::new());
+
+    static STYLESHEET: CssStyleSheet = {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let style = document.create_element("style").unwrap();
+        document.head().unwrap().append_child(&style).unwrap();
+        style
+            .unchecked_into::<HtmlStyleElement>()
+            .sheet()
+            .unwrap()
+            .unchecked_into()
+    };
+}
+
+/// Appends `selector { styles }`, inside `@media (min-width: ..rem)` when a width is given.
+/// Later rules win over earlier ones of the same specificity.
+pub fn insert_rule(min_width: Option<f32>, selector: &str, styles: &[(&str, String)]) {
+    let body = styles
+        .iter()
+        .map(|(name, value)| format!("{}: {};", name, value))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let rule = match min_width {
+        Some(width) => format!("@media (min-width: {}rem) {{ {} {{ {} }} }}", width, selector, body),
+        None => format!("{} {{ {} }}", selector, body),
+    };
+
+    STYLESHEET.with(|sheet| {
+        let index = sheet.css_rules().map(|rules| rules.length()).unwrap_or(0);
+        let _ = sheet.insert_rule_with_index(&rule, index);
+    });
+}
+
+fn window_size() -> (f64, f64) {
+    let Some(window) = web_sys::window() else {
+        return (0.0, 0.0);
+    };
+    let dimension = |value: Result<wasm_bindgen::JsValue, _>| value.ok().and_then(|v| v.as_f64()).unwrap_or(0.0);
+    (dimension(window.inner_width()), dimension(window.inner_height()))
+}
+
+/// Inner width and height of the window in CSS pixels
+pub fn window_size_signal() -> impl Signal<Item = (f64, f64)> {
+    WINDOW_SIZE.with(|size| size.value.signal())
+}
+
+fn media_query_mutable(query: &str) -> Mutable<bool> {
+    MEDIA_QUERIES.with(|queries| {
+        queries
+            .borrow_mut()
+            .entry(query.to_string())
+            .or_insert_with(|| {
+                let list = web_sys::window().and_then(|window| window.match_media(query).ok().flatten());
+                let value = Mutable::new(list.as_ref().map(|list| list.matches()).unwrap_or(false));
+                let listener = list.map(|list| {
+                    EventListener::new(&list.clone(), "change", {
+                        let value = value.clone();
+                        move |_| value.set(list.matches())
+                    })
+                });
+                Watched { value, _listener: listener }
+            })
+            .value
+            .clone()
+    })
+}
+
+/// Whether `query` currently matches, e.g. `"(orientation: portrait)"`
+pub fn media_query(query: &str) -> bool {
+    media_query_mutable(query).get()
+}
+
+pub fn media_query_signal(query: &str) -> impl Signal<Item = bool> {
+    media_query_mutable(query).signal()
+}
+
+/// The widest theme breakpoint the viewport has reached, `None` below all of them
+pub fn breakpoint_signal() -> impl Signal<Item = Option<Breakpoint>> {
+    THEME
+        .signal_ref(|theme| Breakpoint::all().map(|breakpoint| breakpoint.width_in(theme)))
+        .dedupe_map(|widths| *widths)
+        .switch(|widths| {
+            let matches = |i: usize| media_query_signal(&format!("(min-width: {}rem)", widths[i]));
+            map_ref! {
+                let sm = matches(0),
+                let md = matches(1),
+                let lg = matches(2),
+                let xl = matches(3) => {
+                    Breakpoint::all()
+                        .into_iter()
+                        .zip(widths)
+                        .zip([*sm, *md, *lg, *xl])
+                        .filter(|(_, matches)| *matches)
+                        .max_by(|((_, a), _), ((_, b), _)| a.total_cmp(b))
+                        .map(|((breakpoint, _), _)| breakpoint)
+                }
+            }
+        })
+        .dedupe()
+}
+
+pub fn prefers_reduced_motion_signal() -> impl Signal<Item = bool> {
+    media_query_signal("(prefers-reduced-motion: reduce)")
+}
@@ -1,7 +1,14 @@
 pub mod colours;
 pub mod js;
+pub mod media;
 pub mod mutable;
+#[cfg(feature = "serde")]
+pub mod persist;
+pub mod router;
 pub mod safety;
 pub mod theme;
+#[cfg(feature = "serde")]
+pub mod tokens;
+pub mod url_state;
 
 pub use theme::Provider;
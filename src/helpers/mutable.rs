@@ -1,62 +1,378 @@
+//! Combining several signals into one signal of tuples, see [`combine!`](crate::combine).
+
 use std::{
     pin::Pin,
     task::{Context, Poll},
 };
 
-use futures_signals::signal::{Mutable, MutableSignalCloned, Signal};
-
-pub struct Mutable2<A, B>(
-    (MutableSignalCloned<A>, Mutable<A>),
-    (MutableSignalCloned<B>, Mutable<B>),
-)
-where
-    A: Clone,
-    B: Clone;
-impl<A, B> Mutable2<A, B>
-where
-    A: Clone,
-    B: Clone,
-{
-    pub fn new(a: Mutable<A>, b: Mutable<B>) -> Self {
-        Mutable2((a.signal_cloned(), a), (b.signal_cloned(), b))
-    }
+use futures_signals::signal::Signal;
+
+type Eq<T> = fn(&T, &T) -> bool;
+
+/// One input of a combinator, with its latest value
+struct Input<S: Signal> {
+    signal: Option<Pin<Box<S>>>,
+    value: Option<S::Item>,
 }
-impl<A, B> Signal for Mutable2<A, B>
-where
-    A: Clone,
-    B: Clone,
-{
-    type Item = (A, B);
-
-    fn poll_change(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        let a = Pin::new(&mut self.0 .0).poll_change(cx);
-        let b = Pin::new(&mut self.1 .0).poll_change(cx);
+
+impl<S: Signal> Input<S> {
+    fn new(signal: S) -> Self {
+        Self {
+            signal: Some(Box::pin(signal)),
+            value: None,
+        }
+    }
+
+    /// Polls until the signal is pending or ended, so its waker is always registered.
+    /// Returns whether the value changed.
+    fn poll(&mut self, cx: &mut Context) -> bool {
         let mut changed = false;
+        while let Some(signal) = &mut self.signal {
+            match signal.as_mut().poll_change(cx) {
+                Poll::Ready(Some(value)) => {
+                    self.value = Some(value);
+                    changed = true;
+                }
+                Poll::Ready(None) => self.signal = None,
+                Poll::Pending => break,
+            }
+        }
+        changed
+    }
 
-        let left_done = match a {
-            Poll::Ready(None) => true,
-            Poll::Ready(_) => {
-                changed = true;
-                false
+    fn ended(&self) -> bool {
+        self.signal.is_none()
+    }
+
+    /// Ended before ever having a value, so the combinator never can
+    fn starved(&self) -> bool {
+        self.ended() && self.value.is_none()
+    }
+}
+
+macro_rules! combinators {
+    ($($(#[$doc:meta])* $name:ident { $($field:ident: $signal:ident),+ })+) => {$(
+        $(#[$doc])*
+        pub struct $name<$($signal: Signal),+> {
+            $($field: Input<$signal>,)+
+            eq: Option<Eq<($($signal::Item,)+)>>,
+            last: Option<($($signal::Item,)+)>,
+        }
+
+        impl<$($signal: Signal),+> $name<$($signal),+> {
+            #[allow(clippy::too_many_arguments)]
+            pub fn new($($field: $signal),+) -> Self {
+                Self {
+                    $($field: Input::new($field),)+
+                    eq: None,
+                    last: None,
+                }
+            }
+
+            /// Skips tuples equal to the previously emitted one
+            pub fn dedupe(mut self) -> Self
+            where
+                $($signal::Item: PartialEq,)+
+            {
+                self.eq = Some(|a, b| a == b);
+                self
             }
-            Poll::Pending => false,
-        };
-
-        let right_done = match b {
-            Poll::Ready(None) => true,
-            Poll::Ready(_) => {
-                changed = true;
-                false
+        }
+
+        // Inputs are boxed, so nothing structurally pinned is ever moved
+        impl<$($signal: Signal),+> Unpin for $name<$($signal),+> {}
+
+        impl<$($signal),+> Signal for $name<$($signal),+>
+        where
+            $($signal: Signal, $signal::Item: Clone,)+
+        {
+            type Item = ($($signal::Item,)+);
+
+            fn poll_change(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+                let this = self.get_mut();
+                let changed = [$(this.$field.poll(cx)),+].contains(&true);
+
+                if changed {
+                    if let ($(Some($field),)+) = ($(&this.$field.value,)+) {
+                        let item = ($($field.clone(),)+);
+                        match (this.eq, &this.last) {
+                            (Some(eq), Some(last)) if eq(last, &item) => {}
+                            (Some(_), _) => {
+                                this.last = Some(item.clone());
+                                return Poll::Ready(Some(item));
+                            }
+                            (None, _) => return Poll::Ready(Some(item)),
+                        }
+                    }
+                }
+
+                if $(this.$field.starved())||+ || ($(this.$field.ended())&&+) {
+                    Poll::Ready(None)
+                } else {
+                    Poll::Pending
+                }
             }
-            Poll::Pending => false,
-        };
+        }
+    )+};
+}
+
+combinators! {
+    /// Two signals combined, see [`combine!`](crate::combine)
+    Combine2 { a: A, b: B }
+    /// Three signals combined, see [`combine!`](crate::combine)
+    Combine3 { a: A, b: B, c: C }
+    /// Four signals combined, see [`combine!`](crate::combine)
+    Combine4 { a: A, b: B, c: C, d: D }
+    /// Five signals combined, see [`combine!`](crate::combine)
+    Combine5 { a: A, b: B, c: C, d: D, e: E }
+    /// Six signals combined, see [`combine!`](crate::combine)
+    Combine6 { a: A, b: B, c: C, d: D, e: E, f: F }
+    /// Seven signals combined, see [`combine!`](crate::combine)
+    Combine7 { a: A, b: B, c: C, d: D, e: E, f: F, g: G }
+    /// Eight signals combined, see [`combine!`](crate::combine)
+    Combine8 { a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H }
+}
+
+/// Combines 2 to 8 signals into a signal of tuples.
+///
+/// It first emits once every input has a value, then whenever any input changes,
+/// cloning only the latest values. It ends once every input has ended, or when an
+/// input ends without ever having a value. Call `.dedupe()` to skip unchanged tuples.
+///
+/// ```ignore
+/// combine!(data.signal_cloned(), sort.signal_cloned()).dedupe()
+/// ```
+#[macro_export]
+macro_rules! combine {
+    ($a:expr, $b:expr $(,)?) => {
+        $crate::helpers::mutable::Combine2::new($a, $b)
+    };
+    ($a:expr, $b:expr, $c:expr $(,)?) => {
+        $crate::helpers::mutable::Combine3::new($a, $b, $c)
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr $(,)?) => {
+        $crate::helpers::mutable::Combine4::new($a, $b, $c, $d)
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr, $e:expr $(,)?) => {
+        $crate::helpers::mutable::Combine5::new($a, $b, $c, $d, $e)
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr, $e:expr, $f:expr $(,)?) => {
+        $crate::helpers::mutable::Combine6::new($a, $b, $c, $d, $e, $f)
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr, $e:expr, $f:expr, $g:expr $(,)?) => {
+        $crate::helpers::mutable::Combine7::new($a, $b, $c, $d, $e, $f, $g)
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr, $e:expr, $f:expr, $g:expr, $h:expr $(,)?) => {
+        $crate::helpers::mutable::Combine8::new($a, $b, $c, $d, $e, $f, $g, $h)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::{Wake, Waker},
+    };
+
+    use futures_signals::signal::{always, Mutable, SignalExt};
+
+    use super::*;
+
+    /// Counts how often it is woken
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    struct Poller {
+        counter: Arc<CountingWaker>,
+        waker: Waker,
+    }
+
+    impl Poller {
+        fn new() -> Self {
+            let counter = Arc::new(CountingWaker::default());
+            let waker = Waker::from(counter.clone());
+            Self { counter, waker }
+        }
+
+        fn poll<S: Signal + Unpin>(&self, signal: &mut S) -> Poll<Option<S::Item>> {
+            Pin::new(signal).poll_change(&mut Context::from_waker(&self.waker))
+        }
+
+        fn wakes(&self) -> usize {
+            self.counter.0.load(Ordering::SeqCst)
+        }
+    }
+
+    /// Signal returning a fixed sequence of polls, then pending
+    struct Scripted<T>(VecDeque<Poll<Option<T>>>);
 
-        if changed {
-            Poll::Ready(Some((self.0 .1.get_cloned(), self.1 .1.get_cloned())))
-        } else if left_done && right_done {
-            Poll::Ready(None)
-        } else {
-            Poll::Pending
+    impl<T> Scripted<T> {
+        fn new(polls: impl IntoIterator<Item = Poll<Option<T>>>) -> Self {
+            Self(polls.into_iter().collect())
         }
     }
+
+    impl<T> Unpin for Scripted<T> {}
+
+    impl<T> Signal for Scripted<T> {
+        type Item = T;
+
+        fn poll_change(mut self: Pin<&mut Self>, _: &mut Context) -> Poll<Option<T>> {
+            self.0.pop_front().unwrap_or(Poll::Pending)
+        }
+    }
+
+    #[test]
+    fn emits_initial_values_then_waits() {
+        let a = Mutable::new(1);
+        let b = Mutable::new("x");
+        let mut combined = combine!(a.signal(), b.signal());
+        let poller = Poller::new();
+
+        assert_eq!(poller.poll(&mut combined), Poll::Ready(Some((1, "x"))));
+        assert_eq!(poller.poll(&mut combined), Poll::Pending);
+    }
+
+    #[test]
+    fn emits_and_wakes_when_any_input_changes() {
+        let a = Mutable::new(1);
+        let b = Mutable::new(2);
+        let mut combined = combine!(a.signal(), b.signal());
+        let poller = Poller::new();
+
+        assert_eq!(poller.poll(&mut combined), Poll::Ready(Some((1, 2))));
+        assert_eq!(poller.poll(&mut combined), Poll::Pending);
+
+        b.set(3);
+        assert!(poller.wakes() > 0);
+        assert_eq!(poller.poll(&mut combined), Poll::Ready(Some((1, 3))));
+
+        a.set(4);
+        b.set(5);
+        assert_eq!(poller.poll(&mut combined), Poll::Ready(Some((4, 5))));
+        assert_eq!(poller.poll(&mut combined), Poll::Pending);
+    }
+
+    #[test]
+    fn waits_until_every_input_has_a_value() {
+        let mut combined = combine!(
+            always(1),
+            Scripted::new([Poll::Pending, Poll::Ready(Some('b'))])
+        );
+        let poller = Poller::new();
+
+        assert_eq!(poller.poll(&mut combined), Poll::Pending);
+        assert_eq!(poller.poll(&mut combined), Poll::Ready(Some((1, 'b'))));
+    }
+
+    #[test]
+    fn ends_once_every_input_has_ended() {
+        let mut combined = combine!(
+            Scripted::new([Poll::Ready(Some(1)), Poll::Ready(None)]),
+            Scripted::new([Poll::Ready(Some(2)), Poll::Pending, Poll::Ready(None)])
+        );
+        let poller = Poller::new();
+
+        assert_eq!(poller.poll(&mut combined), Poll::Ready(Some((1, 2))));
+        assert_eq!(poller.poll(&mut combined), Poll::Ready(None));
+    }
+
+    #[test]
+    fn keeps_going_while_any_input_is_alive() {
+        let b = Mutable::new(2);
+        let mut combined = combine!(always(1), b.signal());
+        let poller = Poller::new();
+
+        assert_eq!(poller.poll(&mut combined), Poll::Ready(Some((1, 2))));
+        assert_eq!(poller.poll(&mut combined), Poll::Pending);
+
+        b.set(3);
+        assert_eq!(poller.poll(&mut combined), Poll::Ready(Some((1, 3))));
+
+        drop(b);
+        assert_eq!(poller.poll(&mut combined), Poll::Ready(None));
+    }
+
+    #[test]
+    fn ends_when_an_input_ends_without_a_value() {
+        let a = Mutable::new(1);
+        let mut combined = combine!(a.signal(), Scripted::<u8>::new([Poll::Ready(None)]));
+        let poller = Poller::new();
+
+        assert_eq!(poller.poll(&mut combined), Poll::Ready(None));
+    }
+
+    #[test]
+    fn dedupe_skips_unchanged_tuples() {
+        let a = Mutable::new(1);
+        let b = Mutable::new(2);
+        let mut plain = combine!(a.signal(), b.signal());
+        let mut deduped = combine!(a.signal(), b.signal()).dedupe();
+        let poller = Poller::new();
+
+        assert_eq!(poller.poll(&mut plain), Poll::Ready(Some((1, 2))));
+        assert_eq!(poller.poll(&mut deduped), Poll::Ready(Some((1, 2))));
+
+        a.set(1);
+        assert_eq!(poller.poll(&mut plain), Poll::Ready(Some((1, 2))));
+        assert_eq!(poller.poll(&mut deduped), Poll::Pending);
+
+        a.set(5);
+        assert_eq!(poller.poll(&mut deduped), Poll::Ready(Some((5, 2))));
+    }
+
+    #[test]
+    fn combines_arbitrary_signals() {
+        let a = Mutable::new(2);
+        let b = Mutable::new("two".to_string());
+        let mut combined = combine!(
+            a.signal().map(|a| a * 10),
+            b.signal_cloned(),
+            always(true),
+            a.signal_ref(|a| a % 2 == 0),
+        );
+        let poller = Poller::new();
+
+        assert_eq!(
+            poller.poll(&mut combined),
+            Poll::Ready(Some((20, "two".to_string(), true, true)))
+        );
+
+        a.set(3);
+        assert_eq!(
+            poller.poll(&mut combined),
+            Poll::Ready(Some((30, "two".to_string(), true, false)))
+        );
+    }
+
+    #[test]
+    fn supports_eight_inputs() {
+        let mut combined = combine!(
+            always(1),
+            always(2),
+            always(3),
+            always(4),
+            always(5),
+            always(6),
+            always(7),
+            always(8)
+        );
+        let poller = Poller::new();
+
+        assert_eq!(
+            poller.poll(&mut combined),
+            Poll::Ready(Some((1, 2, 3, 4, 5, 6, 7, 8)))
+        );
+        assert_eq!(poller.poll(&mut combined), Poll::Ready(None));
+    }
 }
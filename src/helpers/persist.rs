@@ -0,0 +1,159 @@
+//! Mutables that survive reloads by mirroring themselves to web storage,
+//! enabled with the `serde` feature.
+//!
+//! ```ignore
+//! let sidebar = Persisted::new(PersistKey::new("sidebar").version(2), SidebarState::Open);
+//! sidebar.set(SidebarState::Closed);
+//! ```
+
+use std::{future::Future, ops::Deref};
+
+use futures_signals::signal::{Mutable, SignalExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Storage {
+    /// Kept across browser sessions
+    #[default]
+    Local,
+    /// Cleared when the tab is closed
+    Session,
+}
+
+impl Storage {
+    fn get(self) -> Option<web_sys::Storage> {
+        let window = web_sys::window()?;
+        match self {
+            Storage::Local => window.local_storage(),
+            Storage::Session => window.session_storage(),
+        }
+        .ok()
+        .flatten()
+    }
+}
+
+/// Where a value is stored. Bump the version whenever the stored type changes
+/// so values written by older builds are discarded instead of misread.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct PersistKey {
+    key: String,
+    storage: Storage,
+    version: u32,
+}
+
+impl PersistKey {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn storage(mut self, storage: Storage) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    pub fn session(self) -> Self {
+        self.storage(Storage::Session)
+    }
+
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+}
+
+impl From<&str> for PersistKey {
+    fn from(key: &str) -> Self {
+        PersistKey::new(key)
+    }
+}
+
+impl From<String> for PersistKey {
+    fn from(key: String) -> Self {
+        PersistKey::new(&key)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Stored<T> {
+    version: u32,
+    value: T,
+}
+
+/// A [`Mutable`] restored from web storage on creation and written back on every change
+pub struct Persisted<T> {
+    key: PersistKey,
+    mutable: Mutable<T>,
+}
+
+impl<T> Persisted<T>
+where
+    T: Serialize + DeserializeOwned + Clone + 'static,
+{
+    /// A mutable written back for the rest of the page's life,
+    /// use [`bind`](Self::bind) for state that belongs to an element
+    pub fn new(key: impl Into<PersistKey>, default: T) -> Self {
+        let key = key.into();
+        let mutable = Mutable::new(default);
+        wasm_bindgen_futures::spawn_local(Self::bind(key.clone(), mutable.clone()));
+        Self { key, mutable }
+    }
+
+    /// Persists an existing mutable, replacing its value with the stored one if there is one.
+    /// The returned future writes every change back, tie it to an element with `.future(..)`.
+    pub fn bind(key: impl Into<PersistKey>, mutable: Mutable<T>) -> impl Future<Output = ()> {
+        let key = key.into();
+        if let Some(value) = Self::read(&key) {
+            mutable.set(value);
+        }
+
+        mutable.signal_cloned().for_each(move |value| {
+            Self::write(&key, value);
+            async {}
+        })
+    }
+
+    pub fn mutable(&self) -> Mutable<T> {
+        self.mutable.clone()
+    }
+
+    /// Removes the stored value, the current value is kept until the next change
+    pub fn clear(&self) {
+        if let Some(storage) = self.key.storage.get() {
+            let _ = storage.remove_item(&self.key.key);
+        }
+    }
+
+    /// The stored value, discarding it if it is from another version or no longer parses
+    fn read(key: &PersistKey) -> Option<T> {
+        let storage = key.storage.get()?;
+        let json = storage.get_item(&key.key).ok()??;
+        match serde_json::from_str::<Stored<T>>(&json) {
+            Ok(stored) if stored.version == key.version => Some(stored.value),
+            _ => {
+                let _ = storage.remove_item(&key.key);
+                None
+            }
+        }
+    }
+
+    fn write(key: &PersistKey, value: T) {
+        let stored = Stored {
+            version: key.version,
+            value,
+        };
+        if let (Some(storage), Ok(json)) = (key.storage.get(), serde_json::to_string(&stored)) {
+            let _ = storage.set_item(&key.key, &json);
+        }
+    }
+}
+
+impl<T> Deref for Persisted<T> {
+    type Target = Mutable<T>;
+
+    fn deref(&self) -> &Mutable<T> {
+        &self.mutable
+    }
+}
@@ -0,0 +1,402 @@
+//! A small client-side router on top of the History API.
+//!
+//! ```ignore
+//! let router = Router::new()
+//!     .route(Route::new("/", |_| home()))
+//!     .route(Route::new("/users", |m| users(m.outlet())).child(Route::new(":id", |m| user(m.param("id")))))
+//!     .fallback(not_found);
+//! Shell::new().router(router).dom()
+//! ```
+//!
+//! Patterns are `/`-separated segments: literals, `:name` parameters and a
+//! trailing `*name` (or `*`) capturing the rest of the path. Child patterns are
+//! relative to their parent, which renders them through [`RouteMatch::outlet`].
+
+use std::{collections::HashMap, rc::Rc};
+
+use dominator::{html, Dom};
+use futures_signals::signal::{Signal, SignalExt};
+use gloo::events::{EventListener, EventListenerOptions};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, MouseEvent, Url};
+
+use crate::helpers::url_state::{self, current_href, HistoryMode};
+
+pub type RouteParams = HashMap<String, String>;
+
+type View = Rc<dyn Fn(&RouteMatch) -> Dom>;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Param(String),
+    Rest(String),
+}
+
+#[derive(Clone)]
+pub struct Route {
+    segments: Vec<Segment>,
+    view: View,
+    children: Rc<Vec<Route>>,
+}
+
+impl Route {
+    pub fn new(pattern: &str, view: impl Fn(&RouteMatch) -> Dom + 'static) -> Self {
+        Self {
+            segments: split(pattern)
+                .map(|segment| match segment.chars().next() {
+                    Some(':') => Segment::Param(segment[1..].to_string()),
+                    Some('*') => Segment::Rest(segment[1..].to_string()),
+                    _ => Segment::Literal(segment.to_string()),
+                })
+                .collect(),
+            view: Rc::new(view),
+            children: Rc::new(Vec::new()),
+        }
+    }
+
+    /// Adds a nested route, matched against the path left over by this one
+    pub fn child(mut self, route: Route) -> Self {
+        Rc::make_mut(&mut self.children).push(route);
+        self
+    }
+
+    /// Matches the start of `path`, returning the captured parameters and the segments used
+    fn matches(&self, path: &[&str]) -> Option<(RouteParams, usize)> {
+        let mut params = RouteParams::new();
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Rest(name) => {
+                    if !name.is_empty() {
+                        params.insert(name.clone(), path[i.min(path.len())..].join("/"));
+                    }
+                    return Some((params, path.len()));
+                }
+                Segment::Literal(literal) => (path.get(i)? == literal).then_some(())?,
+                Segment::Param(name) => {
+                    params.insert(name.clone(), path.get(i)?.to_string());
+                }
+            }
+        }
+
+        let used = self.segments.len();
+        (used == path.len() || !self.children.is_empty()).then_some((params, used))
+    }
+
+    /// Whether this route, through its children where it leaves segments, matches the whole of `path`
+    fn matches_all(&self, path: &[&str]) -> bool {
+        self.matches(path).is_some_and(|(_, used)| {
+            used == path.len() || self.children.iter().any(|child| child.matches_all(&path[used..]))
+        })
+    }
+}
+
+/// A matched route, handed to its view
+pub struct RouteMatch {
+    /// Parameters of this route and its parents
+    pub params: RouteParams,
+    outlet: Outlet,
+}
+
+impl RouteMatch {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(String::as_str)
+    }
+
+    /// Renders the matched child route
+    pub fn outlet(&self) -> Dom {
+        self.outlet.dom()
+    }
+}
+
+/// Renders whichever of its routes matches the path below `depth`
+#[derive(Clone)]
+struct Outlet {
+    routes: Rc<Vec<Route>>,
+    depth: usize,
+    params: RouteParams,
+    fallback: Option<Rc<dyn Fn() -> Dom>>,
+}
+
+impl Outlet {
+    /// The index of the matched route, all parameters so far and the depth its children start at.
+    /// A parent only matches when its children can take the segments it leaves.
+    fn find(&self, path: &[&str]) -> Option<(usize, RouteParams, usize)> {
+        let rest = &path[self.depth.min(path.len())..];
+        self.routes.iter().enumerate().find_map(|(index, route)| {
+            if !route.matches_all(rest) {
+                return None;
+            }
+            let (params, used) = route.matches(rest)?;
+            let mut all = self.params.clone();
+            all.extend(params);
+            Some((index, all, self.depth + used))
+        })
+    }
+
+    fn dom(&self) -> Dom {
+        let outlet = self.clone();
+        html!("div", {
+            .style("display", "contents")
+            .child_signal(path_signal().map({
+                let outlet = outlet.clone();
+                move |path| {
+                    let path = segments(&path);
+                    let path = path.iter().map(String::as_str).collect::<Vec<&str>>();
+                    // Below a parent that used up the whole path nothing is missing
+                    let missing = outlet.depth == 0 || outlet.depth < path.len();
+                    outlet.find(&path).ok_or(missing)
+                }
+            }).dedupe_cloned().map(move |found| {
+                // Without a fallback an unmatched path renders nothing
+                match found {
+                    Ok((index, params, depth)) => {
+                        let route = &outlet.routes[index];
+                        Some((route.view)(&RouteMatch {
+                            params: params.clone(),
+                            outlet: Outlet {
+                                routes: route.children.clone(),
+                                depth,
+                                params,
+                                fallback: outlet.fallback.clone(),
+                            },
+                        }))
+                    }
+                    Err(true) => outlet.fallback.as_ref().map(|fallback| fallback()),
+                    Err(false) => None,
+                }
+            }))
+        })
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Router {
+    routes: Vec<Route>,
+    fallback: Option<Rc<dyn Fn() -> Dom>>,
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn route(mut self, route: Route) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    /// Rendered when no route matches the whole path, otherwise nothing is
+    pub fn fallback(mut self, view: impl Fn() -> Dom + 'static) -> Self {
+        self.fallback = Some(Rc::new(view));
+        self
+    }
+
+    /// Renders the matched route, turning same-origin link clicks into in-app navigation
+    pub fn dom(&self) -> Dom {
+        intercept_links();
+        Outlet {
+            routes: Rc::new(self.routes.clone()),
+            depth: 0,
+            params: RouteParams::new(),
+            fallback: self.fallback.clone(),
+        }
+        .dom()
+    }
+}
+
+fn split(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+/// The decoded segments of a path. Decoding after splitting keeps an encoded `/` within its segment.
+fn segments(path: &str) -> Vec<String> {
+    split(path).map(decode_segment).collect()
+}
+
+/// Decodes `%XX` escapes, keeping the segment as it is when they don't form UTF-8
+fn decode_segment(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| bytes[i] == b'%' && hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| segment.to_string())
+}
+
+/// The current path, without query or fragment
+pub fn path_signal() -> impl Signal<Item = String> {
+    url_state::location_signal()
+        .map(|href| Url::new(&href).map(|url| url.pathname()).unwrap_or_default())
+        .dedupe_cloned()
+}
+
+/// Pushes `href`, resolved against the current URL, onto the history
+pub fn navigate(href: &str) {
+    url_state::navigate(href, HistoryMode::Push);
+}
+
+/// Whether `href` points at the current page or one of its descendants.
+/// Links with a fragment only match that fragment.
+pub fn is_active(href: &str) -> bool {
+    let current = current_href();
+    let (Ok(link), Ok(current)) = (Url::new_with_base(href, &current), Url::new(&current)) else {
+        return false;
+    };
+    let (path, current_path) = (link.pathname(), current.pathname());
+    let path_matches = path == current_path
+        || (path != "/" && current_path.starts_with(&format!("{}/", path.trim_end_matches('/'))));
+    path_matches && (link.hash().is_empty() || link.hash() == current.hash())
+}
+
+pub fn is_active_signal(href: &str) -> impl Signal<Item = bool> {
+    let href = href.to_string();
+    url_state::location_signal().map(move |_| is_active(&href)).dedupe()
+}
+
+thread_local! {
+    static LINKS: Option<EventListener> = web_sys::window().and_then(|window| window.document()).map(|document| {
+        EventListener::new_with_options(&document, "click", EventListenerOptions::enable_prevent_default(), |event| {
+            let Some(event) = event.dyn_ref::<MouseEvent>() else {
+                return;
+            };
+            if event.default_prevented()
+                || event.button() != 0
+                || event.ctrl_key()
+                || event.meta_key()
+                || event.shift_key()
+                || event.alt_key()
+            {
+                return;
+            }
+            let Some(link) = event
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+                .and_then(|target| target.closest("a[href]").ok().flatten())
+            else {
+                return;
+            };
+            if link.has_attribute("download") || link.get_attribute("target").is_some_and(|target| target != "_self") {
+                return;
+            }
+
+            let current = current_href();
+            let (Some(href), Ok(current)) = (link.get_attribute("href"), Url::new(&current)) else {
+                return;
+            };
+            let Ok(url) = Url::new_with_base(&href, &current.href()) else {
+                return;
+            };
+            // Fragment links on the same page are left to the browser so it scrolls
+            let same_page = url.pathname() == current.pathname() && url.search() == current.search();
+            if url.origin() != current.origin() || (same_page && !url.hash().is_empty()) {
+                return;
+            }
+
+            event.prevent_default();
+            navigate(&url.href());
+        })
+    });
+}
+
+/// Handles clicks on same-origin links with [`navigate`] instead of a page load, once per page
+pub fn intercept_links() {
+    LINKS.with(|_| {});
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::{decode_segment, segments, Outlet, Route, RouteParams};
+
+    fn route(pattern: &str) -> Route {
+        Route::new(pattern, |_| unreachable!())
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> RouteParams {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn static_routes() {
+        assert_eq!(route("/").matches(&[]), Some((params(&[]), 0)));
+        assert_eq!(route("/").matches(&["about"]), None);
+        assert_eq!(route("/about/team").matches(&["about", "team"]), Some((params(&[]), 2)));
+        assert_eq!(route("/about/team").matches(&["about"]), None);
+        assert_eq!(route("/about").matches(&["about", "team"]), None);
+    }
+
+    #[test]
+    fn parameters() {
+        assert_eq!(
+            route("/users/:id/posts/:post").matches(&["users", "7", "posts", "hello"]),
+            Some((params(&[("id", "7"), ("post", "hello")]), 4))
+        );
+        assert_eq!(route("/users/:id").matches(&["users"]), None);
+        assert_eq!(route("/users/:id").matches(&["teams", "7"]), None);
+    }
+
+    #[test]
+    fn wildcards() {
+        assert_eq!(route("/files/*path").matches(&["files", "a", "b.txt"]), Some((params(&[("path", "a/b.txt")]), 3)));
+        assert_eq!(route("/files/*path").matches(&["files"]), Some((params(&[("path", "")]), 1)));
+        assert_eq!(route("*").matches(&["anything", "at", "all"]), Some((params(&[]), 3)));
+    }
+
+    #[test]
+    fn parents_consume_their_prefix() {
+        let parent = route("/users/:id").child(route("settings"));
+        let path = ["users", "7", "settings"];
+        let (found, used) = parent.matches(&path).unwrap();
+        assert_eq!((found, used), (params(&[("id", "7")]), 2));
+        assert_eq!(parent.children[0].matches(&path[used..]), Some((params(&[]), 1)));
+
+        // A leaf needs the whole path, a parent only its own segments
+        assert_eq!(route("/users/:id").matches(&path), None);
+        assert_eq!(parent.matches(&["users", "7"]), Some((params(&[("id", "7")]), 2)));
+    }
+
+    fn outlet(routes: Vec<Route>) -> Outlet {
+        Outlet {
+            routes: Rc::new(routes),
+            depth: 0,
+            params: RouteParams::new(),
+            fallback: None,
+        }
+    }
+
+    #[test]
+    fn parents_need_a_child_for_the_rest() {
+        let outlet = outlet(vec![route("/users").child(route(":id").child(route("settings")))]);
+        assert_eq!(outlet.find(&["users"]), Some((0, params(&[]), 1)));
+        assert_eq!(outlet.find(&["users", "7"]), Some((0, params(&[]), 1)));
+        assert_eq!(outlet.find(&["users", "7", "settings"]), Some((0, params(&[]), 1)));
+        assert_eq!(outlet.find(&["users", "7", "garbage"]), None);
+        assert_eq!(outlet.find(&["users", "7", "settings", "garbage"]), None);
+    }
+
+    #[test]
+    fn segments_are_decoded() {
+        assert_eq!(segments("/users/J%C3%BCrgen%20K/a%2Fb"), ["users", "Jürgen K", "a/b"]);
+        assert_eq!(decode_segment("100%"), "100%");
+        assert_eq!(decode_segment("%zz%4"), "%zz%4");
+        assert_eq!(decode_segment("%2b%2B"), "++");
+        // Escapes that aren't UTF-8 leave the segment encoded
+        assert_eq!(decode_segment("%FF"), "%FF");
+    }
+}
@@ -1,46 +1,362 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Mutex};
 
-use dominator::{html, Dom};
-use futures_signals::signal::{Mutable, SignalExt};
+use dominator::{html, with_node, Dom};
+use futures_signals::{
+    map_ref,
+    signal::{Mutable, Signal, SignalExt},
+};
 use lazy_static::lazy_static;
+use wasm_bindgen::JsCast;
+use web_sys::{CssStyleDeclaration, Element, HtmlElement};
 
-use crate::components::ty::{Colour, RemSizing};
+use crate::{
+    components::ty::{Breakpoint, Colour, Length},
+    helpers::{
+        colours::{
+            best_text_on, best_text_on_translucent, contrast_ratio, Deficiency, InvalidColour, Rgba, BLACK, SHADE_STEPS, WCAG_AA, WHITE,
+        },
+        media::{media_query, media_query_signal},
+        safety::rand_id,
+    },
+};
 
 lazy_static! {
+    /// Theme used outside of any Provider, written onto `:root` as CSS variables
+    /// once a component first reads a theme value
     pub static ref THEME: Mutable<Theme> = Mutable::new(Theme::default());
+    /// Scheme used outside of any Provider, always Light or Dark
+    pub static ref SCHEME: Mutable<ColourScheme> = Mutable::new(ColourScheme::Light);
+    /// Light palette of `Theme::default()`, the last resort when resolving a colour
+    pub static ref DEFAULT_PALETTE: HashMap<Colour, String> = Theme::default().palette(ColourScheme::Light);
+    static ref PROVIDERS: Mutex<HashMap<String, ThemeContext>> = Mutex::new(HashMap::new());
 }
 
-#[derive(Clone)]
+thread_local! {
+    static ROOT_THEME: () = {
+        let root = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.document_element())
+            .and_then(|root| root.dyn_into::<HtmlElement>().ok());
+        if let Some(root) = root {
+            let mut applied = Vec::new();
+            wasm_bindgen_futures::spawn_local(
+                map_ref! {
+                    let theme = THEME.signal_cloned(),
+                    let scheme = SCHEME.signal(),
+                    let prefers_dark = media_query_signal(PREFERS_DARK) => (theme.clone(), scheme.resolve(*prefers_dark))
+                }
+                .for_each(move |(theme, scheme)| {
+                    apply_variables(&root.style(), &mut applied, &theme, scheme);
+                    async {}
+                }),
+            );
+        }
+    };
+}
+
+/// Starts keeping `THEME` and `SCHEME` written onto `:root`, so components
+/// outside of any Provider follow changes to them
+pub(crate) fn watch_global_theme() {
+    ROOT_THEME.with(|_| {});
+}
+
+/// Replaces the variables listed in `applied` with the theme's
+fn apply_variables(style: &CssStyleDeclaration, applied: &mut Vec<String>, theme: &Theme, scheme: ColourScheme) {
+    for name in applied.drain(..) {
+        let _ = style.remove_property(&name);
+    }
+    for (name, value) in theme.css_variables(scheme) {
+        let _ = style.set_property(&name, &value);
+        applied.push(name);
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColourScheme {
+    #[default]
+    Light,
+    Dark,
+    /// Follows the browser's `prefers-color-scheme`
+    System,
+}
+
+impl ColourScheme {
+    pub fn resolve(self, prefers_dark: bool) -> Self {
+        match self {
+            ColourScheme::System if prefers_dark => ColourScheme::Dark,
+            ColourScheme::System => ColourScheme::Light,
+            scheme => scheme,
+        }
+    }
+
+    /// Page background translucent colours are drawn over
+    pub fn page_colour(self) -> Rgba {
+        match self {
+            ColourScheme::Dark => BLACK,
+            _ => WHITE,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "crate::helpers::tokens::ThemeTokens", try_from = "crate::helpers::tokens::ThemeTokens")
+)]
 pub struct Theme {
     pub colours: Option<HashMap<Colour, String>>,
-    pub sizing: Option<HashMap<RemSizing, f32>>,
+    pub dark_colours: Option<HashMap<Colour, String>>,
+    /// Overrides for individual steps of the generated shade scales
+    pub shades: Option<HashMap<Colour, HashMap<u16, String>>>,
+    pub sizing: Option<HashMap<Length, f32>>,
+    /// Minimum viewport widths in rem, see [`Responsive`](crate::components::ty::Responsive)
+    pub breakpoints: Option<HashMap<Breakpoint, f32>>,
+}
+
+impl Theme {
+    /// Colours for the given scheme, falling back to the light
+    /// palette when no dark palette is set
+    pub fn palette(&self, scheme: ColourScheme) -> HashMap<Colour, String> {
+        match scheme {
+            ColourScheme::Dark => self.dark_colours.clone().or(self.colours.clone()),
+            _ => self.colours.clone(),
+        }
+        .unwrap_or_default()
+    }
+
+    /// This theme as seen with a colour-vision deficiency,
+    /// invalid colours are left untouched
+    pub fn simulate(&self, deficiency: Deficiency) -> Self {
+        let simulate = |value: &String| {
+            Rgba::parse(value)
+                .map(|colour| colour.simulate(deficiency).to_css())
+                .unwrap_or_else(|_| value.clone())
+        };
+        let palette = |colours: &HashMap<Colour, String>| {
+            colours
+                .iter()
+                .map(|(colour, value)| (colour.clone(), simulate(value)))
+                .collect::<HashMap<_, _>>()
+        };
+
+        Self {
+            colours: self.colours.as_ref().map(palette),
+            dark_colours: self.dark_colours.as_ref().map(palette),
+            shades: self.shades.as_ref().map(|shades| {
+                shades
+                    .iter()
+                    .map(|(colour, steps)| {
+                        let steps = steps.iter().map(|(step, value)| (*step, simulate(value)));
+                        (colour.clone(), steps.collect())
+                    })
+                    .collect()
+            }),
+            sizing: self.sizing.clone(),
+            breakpoints: self.breakpoints.clone(),
+        }
+    }
+
+    /// A step of the colour's shade scale, see [`Rgba::shade`].
+    /// Colours that don't parse are returned unshaded.
+    pub fn shade(&self, colour: &Colour, step: u16, scheme: ColourScheme) -> String {
+        self.shades
+            .as_ref()
+            .and_then(|shades| shades.get(colour)?.get(&step).cloned())
+            .unwrap_or_else(|| {
+                let value = colour.resolve(&self.palette(scheme));
+                Rgba::parse(&value)
+                    .map(|parsed| parsed.shade(step, scheme == ColourScheme::Dark).to_css())
+                    .unwrap_or(value)
+            })
+    }
+
+    /// Every colour and size as CSS custom properties, e.g. `--domcom-colour-blue`.
+    /// Each colour also gets `-contrast` and `-contrast-75` variables holding readable
+    /// text colours on it and on it at [`TRANSLUCENT`](crate::helpers::colours::TRANSLUCENT) opacity,
+    /// and `-50` to `-900` variables for its shade scale. Colours that don't parse
+    /// fall back as in [`Colour::resolve`], with a warning.
+    pub fn css_variables(&self, scheme: ColourScheme) -> Vec<(String, String)> {
+        let mut variables = Vec::new();
+
+        let palette = self.palette(scheme);
+        for colour in Colour::all() {
+            let Some(name) = colour.variable() else {
+                continue;
+            };
+            if let Some(value) = palette.get(&colour).filter(|value| Rgba::parse(value).is_err()) {
+                warn(format!("Ignoring {}: {}", name, InvalidColour(value.clone())));
+            }
+            let value = colour.resolve(&palette);
+            let Ok(parsed) = Rgba::parse(&value) else {
+                continue;
+            };
+
+            for step in SHADE_STEPS {
+                variables.push((format!("{}-{}", name, step), self.shade(&colour, step, scheme)));
+            }
+            variables.push((format!("{}-contrast", name), best_text_on(parsed, WHITE, &[BLACK, WHITE]).to_css()));
+            variables.push((
+                format!("{}-contrast-75", name),
+                best_text_on_translucent(parsed, scheme.page_colour()).to_css(),
+            ));
+            variables.push((name, value));
+        }
+
+        for (size, value) in self.sizing.clone().unwrap_or_default() {
+            if let Some(name) = size.variable() {
+                variables.push((name, format!("{}rem", value)));
+            }
+        }
+
+        variables
+    }
 }
 
 impl Default for Theme {
     fn default() -> Self {
         let mut colours: HashMap<Colour, String> = HashMap::new();
+        colours.insert(Colour::Primary, "#5576B9".to_string());
+        colours.insert(Colour::Secondary, "#495057".to_string());
+        colours.insert(Colour::Success, "#2F9E44".to_string());
+        colours.insert(Colour::Warning, "#F08C00".to_string());
+        colours.insert(Colour::Danger, "#E03131".to_string());
+        colours.insert(Colour::Info, "#1098AD".to_string());
         colours.insert(Colour::Grey, "#1E1E1E".to_string());
         colours.insert(Colour::Blue, "#5576B9".to_string());
         colours.insert(Colour::Coral, "#F15A4D".to_string());
         colours.insert(Colour::Pink, "#EE3075".to_string());
+        colours.insert(Colour::Green, "#2F9E44".to_string());
+        colours.insert(Colour::Red, "#E03131".to_string());
+        colours.insert(Colour::Yellow, "#F08C00".to_string());
+        colours.insert(Colour::Teal, "#1098AD".to_string());
+        colours.insert(Colour::Violet, "#7048E8".to_string());
+        colours.insert(Colour::Orange, "#E8590C".to_string());
+
+        let mut dark_colours: HashMap<Colour, String> = HashMap::new();
+        dark_colours.insert(Colour::Primary, "#7C98D3".to_string());
+        dark_colours.insert(Colour::Secondary, "#ADB5BD".to_string());
+        dark_colours.insert(Colour::Success, "#51CF66".to_string());
+        dark_colours.insert(Colour::Warning, "#FCC419".to_string());
+        dark_colours.insert(Colour::Danger, "#FF6B6B".to_string());
+        dark_colours.insert(Colour::Info, "#3BC9DB".to_string());
+        dark_colours.insert(Colour::Grey, "#D4D4D4".to_string());
+        dark_colours.insert(Colour::Blue, "#7C98D3".to_string());
+        dark_colours.insert(Colour::Coral, "#F47E73".to_string());
+        dark_colours.insert(Colour::Pink, "#F2608F".to_string());
+        dark_colours.insert(Colour::Green, "#51CF66".to_string());
+        dark_colours.insert(Colour::Red, "#FF6B6B".to_string());
+        dark_colours.insert(Colour::Yellow, "#FCC419".to_string());
+        dark_colours.insert(Colour::Teal, "#3BC9DB".to_string());
+        dark_colours.insert(Colour::Violet, "#9775FA".to_string());
+        dark_colours.insert(Colour::Orange, "#FF922B".to_string());
 
-        let mut sizing: HashMap<RemSizing, f32> = HashMap::new();
-        sizing.insert(RemSizing::Xs, 0.5);
-        sizing.insert(RemSizing::Sm, 0.75);
-        sizing.insert(RemSizing::Md, 1.0);
-        sizing.insert(RemSizing::Lg, 1.5);
-        sizing.insert(RemSizing::Xl, 2.0);
+        let sizing: HashMap<Length, f32> = [Length::Xs, Length::Sm, Length::Md, Length::Lg, Length::Xl]
+            .into_iter()
+            .filter_map(|size| Some((size.clone(), size.default_rem()?)))
+            .collect();
 
         Self {
             colours: Some(colours),
+            dark_colours: Some(dark_colours),
+            shades: None,
             sizing: Some(sizing),
+            breakpoints: Some(
+                Breakpoint::all()
+                    .into_iter()
+                    .map(|breakpoint| (breakpoint, breakpoint.default_width()))
+                    .collect(),
+            ),
         }
     }
 }
 
+/// The theme and resolved scheme a Provider exposes to its descendants
+#[derive(Clone)]
+pub struct ThemeContext {
+    pub theme: Mutable<Theme>,
+    pub scheme: Mutable<ColourScheme>,
+}
+
+impl ThemeContext {
+    /// Context used outside of any Provider, backed by `THEME` and `SCHEME`
+    pub fn global() -> Self {
+        Self {
+            theme: THEME.clone(),
+            scheme: SCHEME.clone(),
+        }
+    }
+
+    /// Context of the closest ancestor Provider of `element`
+    pub fn closest(element: &Element) -> Self {
+        element
+            .closest("[data-domcom-provider]")
+            .ok()
+            .flatten()
+            .and_then(|provider| provider.get_attribute("data-domcom-provider"))
+            .and_then(|id| PROVIDERS.lock().unwrap().get(&id).cloned())
+            .unwrap_or_else(Self::global)
+    }
+
+    /// Fires with the palette whenever the theme or scheme changes
+    pub fn palette_signal(&self) -> impl Signal<Item = HashMap<Colour, String>> {
+        map_ref! {
+            let theme = self.theme.signal_cloned(),
+            let scheme = self.scheme.signal() => theme.palette(*scheme)
+        }
+    }
+}
+
+/// Warns in debug builds when a component draws text below WCAG AA contrast.
+/// `pair` returns the foreground and background resolved against the palette and scheme
+/// of the closest Provider, or `None` when a colour doesn't parse.
+/// Translucent backgrounds sit on the scheme's page colour.
+pub fn check_contrast<F>(element: &Element, component: &str, pair: F)
+where
+    F: FnOnce(&HashMap<Colour, String>, ColourScheme) -> Option<(Rgba, Rgba)>,
+{
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    let context = ThemeContext::closest(element);
+    let scheme = context.scheme.get();
+    let Some((foreground, background)) = pair(&context.theme.get_cloned().palette(scheme), scheme) else {
+        return;
+    };
+
+    let background = background.over(scheme.page_colour());
+    let ratio = contrast_ratio(foreground.over(background), background);
+    if ratio < WCAG_AA {
+        warn(format!(
+            "{} text {} on {} has a contrast ratio of {:.2}, below WCAG AA ({})",
+            component,
+            foreground,
+            background,
+            ratio,
+            WCAG_AA
+        ));
+    }
+}
+
+/// Logs to the console as plain text, where `console_warn!` would quote it
+fn warn(message: String) {
+    web_sys::console::warn_1(&message.into());
+}
+
+/// Fires with the global palette whenever the theme or scheme changes
+#[deprecated(note = "reads the global theme, ignoring Providers; use `ThemeContext::closest(..).palette_signal()`")]
+pub fn palette_signal() -> impl Signal<Item = HashMap<Colour, String>> {
+    ThemeContext::global().palette_signal()
+}
+
+const PREFERS_DARK: &str = "(prefers-color-scheme: dark)";
+
 #[derive(Default)]
 pub struct Provider {
     theme: Mutable<Theme>,
+    scheme: Mutable<ColourScheme>,
+    deficiency: Mutable<Option<Deficiency>>,
     children: Vec<Dom>,
 }
 
@@ -49,14 +365,35 @@ impl Provider {
         Self::default()
     }
 
+    /// Replaces the parts of the theme that `theme` sets. A light palette without
+    /// a dark one is used for both schemes instead of the previous dark palette.
     pub fn apply_theme(&mut self, theme: Theme) -> &mut Self {
+        let current = self.theme.get_cloned();
+        let dark_colours = match theme.colours {
+            Some(_) => theme.dark_colours,
+            None => theme.dark_colours.or(current.dark_colours),
+        };
         self.theme.set(Theme {
-            colours: theme.colours.or(self.theme.get_cloned().colours),
-            sizing: theme.sizing.or(self.theme.get_cloned().sizing),
+            colours: theme.colours.or(current.colours),
+            dark_colours,
+            shades: theme.shades.or(current.shades),
+            sizing: theme.sizing.or(current.sizing),
+            breakpoints: theme.breakpoints.or(current.breakpoints),
         });
         self
     }
 
+    pub fn colour_scheme(&mut self, scheme: Mutable<ColourScheme>) -> &mut Self {
+        self.scheme = scheme;
+        self
+    }
+
+    /// Previews the theme under a colour-vision deficiency, see [`Theme::simulate`]
+    pub fn simulate(&mut self, deficiency: Mutable<Option<Deficiency>>) -> &mut Self {
+        self.deficiency = deficiency;
+        self
+    }
+
     pub fn child(&mut self, child: Dom) -> &mut Self {
         self.children.push(child);
         self
@@ -67,15 +404,122 @@ impl Provider {
         self
     }
 
-    // TODO : Return a fragment instead of a DOM
-    pub fn dom(mut self) -> Dom {
-        html!("div", { 
-            .child_signal(self.theme.clone().signal_cloned().map(move |theme| {
-            THEME.set(theme.clone());
-            Some(html!("div", {
-                    .children(self.children.iter_mut().map(|c| c))
-                }))
+    /// Renders a `display: contents` element so the theme's CSS variables
+    /// only cascade to this Provider's descendants without affecting layout
+    pub fn dom(&mut self) -> Dom {
+        let id = rand_id();
+        let effective = Mutable::new(self.theme.get_cloned());
+        let resolved = Mutable::new(self.scheme.get().resolve(media_query(PREFERS_DARK)));
+        PROVIDERS.lock().unwrap().insert(
+            id.clone(),
+            ThemeContext {
+                theme: effective.clone(),
+                scheme: resolved.clone(),
+            },
+        );
+
+        html!("div", {
+            .attr("data-domcom-provider", &id)
+            .style("display", "contents")
+            .with_node!(e => {
+                .future({
+                    let mut applied: Vec<String> = Vec::new();
+                    map_ref! {
+                        let theme = self.theme.signal_cloned(),
+                        let deficiency = self.deficiency.signal(),
+                        let scheme = self.scheme.signal(),
+                        let prefers_dark = media_query_signal(PREFERS_DARK) => {
+                            let theme = match deficiency {
+                                Some(deficiency) => theme.simulate(*deficiency),
+                                None => theme.clone(),
+                            };
+                            (theme, scheme.resolve(*prefers_dark))
+                        }
+                    }.for_each(move |(theme, scheme)| {
+                        apply_variables(&e.style(), &mut applied, &theme, scheme);
+                        effective.set(theme);
+                        resolved.set(scheme);
+                        async {}
+                    })
+                })
+            })
+            .after_removed(move |_| {
+                PROVIDERS.lock().unwrap().remove(&id);
+            })
+            .children(self.children.drain(..))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{ColourScheme, Provider, Rgba, Theme};
+    use crate::components::ty::Colour;
+
+    fn palette(pairs: &[(Colour, &str)]) -> HashMap<Colour, String> {
+        pairs.iter().map(|(colour, value)| (colour.clone(), value.to_string())).collect()
+    }
+
+    fn colours(colours: HashMap<Colour, String>) -> Theme {
+        Theme {
+            colours: Some(colours),
+            dark_colours: None,
+            shades: None,
+            sizing: None,
+            breakpoints: None,
+        }
+    }
+
+    #[test]
+    fn light_palette_covers_dark_scheme() {
+        let brand = palette(&[(Colour::Primary, "#123456")]);
+        let mut provider = Provider::new();
+        provider.apply_theme(Theme::default()).apply_theme(colours(brand.clone()));
+        let theme = provider.theme.get_cloned();
+        assert_eq!(theme.palette(ColourScheme::Light), brand);
+        assert_eq!(theme.palette(ColourScheme::Dark), brand);
+    }
 
-        })) })
+    #[test]
+    fn dark_palette_can_be_applied_alone() {
+        let dark = palette(&[(Colour::Primary, "#654321")]);
+        let mut provider = Provider::new();
+        provider.apply_theme(Theme::default()).apply_theme(Theme {
+            colours: None,
+            dark_colours: Some(dark.clone()),
+            ..colours(HashMap::new())
+        });
+        let theme = provider.theme.get_cloned();
+        assert_eq!(theme.palette(ColourScheme::Dark), dark);
+        assert_eq!(theme.palette(ColourScheme::Light), Theme::default().palette(ColourScheme::Light));
+    }
+
+    #[test]
+    fn shade_overrides_win() {
+        let mut steps = HashMap::new();
+        steps.insert(100, "#abcdef".to_string());
+        let theme = Theme {
+            shades: Some([(Colour::Primary, steps)].into_iter().collect()),
+            ..Theme::default()
+        };
+        for scheme in [ColourScheme::Light, ColourScheme::Dark] {
+            assert_eq!(theme.shade(&Colour::Primary, 100, scheme), "#abcdef");
+            assert_ne!(theme.shade(&Colour::Primary, 200, scheme), "#abcdef");
+            assert_ne!(theme.shade(&Colour::Blue, 100, scheme), "#abcdef");
+        }
+    }
+
+    #[test]
+    fn shade_500_is_the_palette_colour() {
+        let theme = Theme::default();
+        for scheme in [ColourScheme::Light, ColourScheme::Dark] {
+            let palette = theme.palette(scheme);
+            for colour in Colour::all() {
+                let base = Rgba::parse(&colour.resolve(&palette)).unwrap().to_css();
+                assert_eq!(theme.shade(&colour, 500, scheme), base, "{:?}", colour);
+            }
+        }
     }
 }
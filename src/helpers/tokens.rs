@@ -0,0 +1,393 @@
+//! Design-token files for [`Theme`], enabled with the `serde` feature.
+//!
+//! ```toml
+//! [colours]
+//! blue = "#5576B9"
+//!
+//! [dark_colours]
+//! blue = "#7C98D3"
+//!
+//! [shades.blue]
+//! 600 = "#3F5F9F"
+//!
+//! [sizing]
+//! md = 1.0
+//!
+//! [breakpoints]
+//! md = 48.0
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt,
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    components::ty::{Breakpoint, Colour, Length},
+    helpers::{
+        colours::{InvalidColour, Rgba},
+        theme::Theme,
+    },
+};
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Json(serde_json::Error),
+    Toml(toml::de::Error),
+    TomlExport(toml::ser::Error),
+    UnknownColour(String),
+    UnknownSize(String),
+    UnknownShade(String),
+    UnknownBreakpoint(String),
+    InvalidColour {
+        token: String,
+        source: InvalidColour,
+    },
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Json(e) => write!(f, "Invalid theme JSON: {}", e),
+            ThemeError::Toml(e) => write!(f, "Invalid theme TOML: {}", e),
+            ThemeError::TomlExport(e) => write!(f, "Unable to export theme as TOML: {}", e),
+            ThemeError::UnknownColour(token) => write!(f, "Unknown colour token: {}", token),
+            ThemeError::UnknownSize(token) => write!(f, "Unknown size token: {}", token),
+            ThemeError::UnknownShade(step) => write!(f, "Unknown shade step: {}", step),
+            ThemeError::UnknownBreakpoint(token) => write!(f, "Unknown breakpoint: {}", token),
+            ThemeError::InvalidColour { token, source } => write!(f, "{} ({})", source, token),
+        }
+    }
+}
+
+impl Error for ThemeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ThemeError::Json(e) => Some(e),
+            ThemeError::Toml(e) => Some(e),
+            ThemeError::TomlExport(e) => Some(e),
+            ThemeError::InvalidColour { source, .. } => Some(source),
+            ThemeError::UnknownColour(_)
+            | ThemeError::UnknownSize(_)
+            | ThemeError::UnknownShade(_)
+            | ThemeError::UnknownBreakpoint(_) => None,
+        }
+    }
+}
+
+/// On-disk layout of a [`Theme`], keyed by token name so exports are stable
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct ThemeTokens {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    colours: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dark_colours: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shades: Option<BTreeMap<String, BTreeMap<String, String>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sizing: Option<BTreeMap<String, f32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    breakpoints: Option<BTreeMap<String, f32>>,
+}
+
+fn colours_from_tokens(
+    tokens: BTreeMap<String, String>,
+) -> Result<HashMap<Colour, String>, ThemeError> {
+    tokens
+        .into_iter()
+        .map(|(token, value)| {
+            let colour =
+                Colour::from_token(&token).ok_or_else(|| ThemeError::UnknownColour(token.clone()))?;
+            Rgba::parse(&value).map_err(|source| ThemeError::InvalidColour { token, source })?;
+            Ok((colour, value))
+        })
+        .collect()
+}
+
+fn shades_from_tokens(
+    tokens: BTreeMap<String, BTreeMap<String, String>>,
+) -> Result<HashMap<Colour, HashMap<u16, String>>, ThemeError> {
+    tokens
+        .into_iter()
+        .map(|(token, steps)| {
+            let colour =
+                Colour::from_token(&token).ok_or_else(|| ThemeError::UnknownColour(token.clone()))?;
+            let steps = steps
+                .into_iter()
+                .map(|(step, value)| {
+                    let step = step
+                        .parse::<u16>()
+                        .map_err(|_| ThemeError::UnknownShade(format!("{}-{}", token, step)))?;
+                    Rgba::parse(&value).map_err(|source| ThemeError::InvalidColour {
+                        token: format!("{}-{}", token, step),
+                        source,
+                    })?;
+                    Ok((step, value))
+                })
+                .collect::<Result<_, ThemeError>>()?;
+            Ok((colour, steps))
+        })
+        .collect()
+}
+
+fn shades_to_tokens(
+    shades: HashMap<Colour, HashMap<u16, String>>,
+) -> BTreeMap<String, BTreeMap<String, String>> {
+    shades
+        .into_iter()
+        .filter_map(|(colour, steps)| {
+            let steps = steps
+                .into_iter()
+                .map(|(step, value)| (step.to_string(), value))
+                .collect();
+            Some((colour.token()?.to_string(), steps))
+        })
+        .collect()
+}
+
+fn colours_to_tokens(colours: HashMap<Colour, String>) -> BTreeMap<String, String> {
+    colours
+        .into_iter()
+        .filter_map(|(colour, value)| Some((colour.token()?.to_string(), value)))
+        .collect()
+}
+
+impl TryFrom<ThemeTokens> for Theme {
+    type Error = ThemeError;
+
+    fn try_from(tokens: ThemeTokens) -> Result<Self, Self::Error> {
+        Ok(Theme {
+            colours: tokens.colours.map(colours_from_tokens).transpose()?,
+            dark_colours: tokens.dark_colours.map(colours_from_tokens).transpose()?,
+            shades: tokens.shades.map(shades_from_tokens).transpose()?,
+            sizing: tokens
+                .sizing
+                .map(|sizing| {
+                    sizing
+                        .into_iter()
+                        .map(|(token, value)| match Length::from_token(&token) {
+                            Some(size) if size.token().is_some() => Ok((size, value)),
+                            _ => Err(ThemeError::UnknownSize(token)),
+                        })
+                        .collect()
+                })
+                .transpose()?,
+            breakpoints: tokens
+                .breakpoints
+                .map(|breakpoints| {
+                    breakpoints
+                        .into_iter()
+                        .map(|(token, value)| match Breakpoint::from_token(&token) {
+                            Some(breakpoint) => Ok((breakpoint, value)),
+                            None => Err(ThemeError::UnknownBreakpoint(token)),
+                        })
+                        .collect()
+                })
+                .transpose()?,
+        })
+    }
+}
+
+impl From<Theme> for ThemeTokens {
+    fn from(theme: Theme) -> Self {
+        ThemeTokens {
+            colours: theme.colours.map(colours_to_tokens),
+            dark_colours: theme.dark_colours.map(colours_to_tokens),
+            shades: theme.shades.map(shades_to_tokens),
+            sizing: theme.sizing.map(|sizing| {
+                sizing
+                    .into_iter()
+                    .filter_map(|(size, value)| Some((size.token()?.to_string(), value)))
+                    .collect()
+            }),
+            breakpoints: theme.breakpoints.map(|breakpoints| {
+                breakpoints
+                    .into_iter()
+                    .map(|(breakpoint, value)| (breakpoint.token().to_string(), value))
+                    .collect()
+            }),
+        }
+    }
+}
+
+impl Theme {
+    pub fn from_json(json: &str) -> Result<Self, ThemeError> {
+        serde_json::from_str::<ThemeTokens>(json)
+            .map_err(ThemeError::Json)?
+            .try_into()
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, ThemeError> {
+        toml::from_str::<ThemeTokens>(toml)
+            .map_err(ThemeError::Toml)?
+            .try_into()
+    }
+
+    pub fn to_json(&self) -> Result<String, ThemeError> {
+        serde_json::to_string_pretty(&ThemeTokens::from(self.clone())).map_err(ThemeError::Json)
+    }
+
+    pub fn to_toml(&self) -> Result<String, ThemeError> {
+        toml::to_string_pretty(&ThemeTokens::from(self.clone())).map_err(ThemeError::TomlExport)
+    }
+}
+
+impl Serialize for Colour {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Colour::Hex(value) => serializer.serialize_str(&value.to_css()),
+            _ => serializer.serialize_str(self.token().unwrap()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Colour {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let token = String::deserialize(deserializer)?;
+        match Colour::from_token(&token) {
+            Some(colour) => Ok(colour),
+            None => Rgba::parse(&token).map(Colour::Hex).map_err(de::Error::custom),
+        }
+    }
+}
+
+impl Serialize for Length {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Length::Rem(rem) => serializer.serialize_f32(*rem),
+            _ => serializer.serialize_str(&self.token_css()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Length {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SizeVisitor;
+
+        impl de::Visitor<'_> for SizeVisitor {
+            type Value = Length;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a size token, a CSS length or a rem value")
+            }
+
+            fn visit_str<E: de::Error>(self, token: &str) -> Result<Self::Value, E> {
+                token
+                    .parse::<Length>()
+                    .map_err(|_| E::custom(ThemeError::UnknownSize(token.to_string())))
+            }
+
+            fn visit_f64<E: de::Error>(self, rem: f64) -> Result<Self::Value, E> {
+                Ok(Length::Rem(rem as f32))
+            }
+
+            fn visit_i64<E: de::Error>(self, rem: i64) -> Result<Self::Value, E> {
+                Ok(Length::Rem(rem as f32))
+            }
+
+            fn visit_u64<E: de::Error>(self, rem: u64) -> Result<Self::Value, E> {
+                Ok(Length::Rem(rem as f32))
+            }
+        }
+
+        deserializer.deserialize_any(SizeVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::ThemeError;
+    use crate::{
+        components::ty::{Breakpoint, CalcOp, Colour, Length},
+        helpers::theme::Theme,
+    };
+
+    fn assert_same(a: &Theme, b: &Theme) {
+        assert_eq!(a.colours, b.colours);
+        assert_eq!(a.dark_colours, b.dark_colours);
+        assert_eq!(a.shades, b.shades);
+        assert_eq!(a.sizing, b.sizing);
+        assert_eq!(a.breakpoints, b.breakpoints);
+    }
+
+    fn custom_theme() -> Theme {
+        Theme {
+            shades: Some(HashMap::from([(Colour::Blue, HashMap::from([(600, "#3F5F9F".to_string())]))])),
+            sizing: Some(HashMap::from([(Length::Md, 1.25), (Length::Xl, 3.0)])),
+            breakpoints: Some(HashMap::from([(Breakpoint::Md, 50.0)])),
+            ..Theme::default()
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let theme = custom_theme();
+        assert_same(&Theme::from_json(&theme.to_json().unwrap()).unwrap(), &theme);
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let theme = custom_theme();
+        assert_same(&Theme::from_toml(&theme.to_toml().unwrap()).unwrap(), &theme);
+    }
+
+    #[test]
+    fn partial_tokens_leave_the_rest_unset() {
+        let theme = Theme::from_toml("[colours]\nblue = \"rebeccapurple\"\n").unwrap();
+        assert_eq!(theme.colours, Some(HashMap::from([(Colour::Blue, "rebeccapurple".to_string())])));
+        assert!(theme.dark_colours.is_none() && theme.sizing.is_none());
+    }
+
+    #[test]
+    fn errors() {
+        type Check = fn(&ThemeError) -> bool;
+        let cases: [(&str, Check); 6] = [
+            ("{", |e| matches!(e, ThemeError::Json(_))),
+            (r##"{"colours": {"mauve": "#ffffff"}}"##, |e| matches!(e, ThemeError::UnknownColour(t) if t == "mauve")),
+            (r##"{"sizing": {"huge": 4}}"##, |e| matches!(e, ThemeError::UnknownSize(t) if t == "huge")),
+            (r##"{"shades": {"blue": {"dark": "#000000"}}}"##, |e| {
+                matches!(e, ThemeError::UnknownShade(t) if t == "blue-dark")
+            }),
+            (r##"{"breakpoints": {"xxl": 90}}"##, |e| matches!(e, ThemeError::UnknownBreakpoint(t) if t == "xxl")),
+            (r##"{"colours": {"red": "#ggg"}}"##, |e| {
+                matches!(e, ThemeError::InvalidColour { token, .. } if token == "red")
+            }),
+        ];
+        for (json, expected) in cases {
+            let error = Theme::from_json(json).unwrap_err();
+            assert!(expected(&error), "{} gave {:?}", json, error);
+        }
+
+        assert!(matches!(Theme::from_toml("colours = ["), Err(ThemeError::Toml(_))));
+        // Only tables can be the root of a TOML document
+        let export = ThemeError::TomlExport(toml::to_string(&1).unwrap_err());
+        assert!(export.to_string().starts_with("Unable to export theme as TOML"));
+    }
+
+    #[test]
+    fn lengths_round_trip() {
+        let lengths = [
+            Length::None,
+            Length::Md,
+            Length::Rem(1.5),
+            Length::Px(-4.0),
+            Length::Auto,
+            Length::Md + Length::Px(10.0),
+            Length::Lg - Length::Percent(5.0),
+            Length::Calc(Box::new(Length::Xs + Length::Em(1.0)), CalcOp::Sub, Box::new(Length::Px(-2.0))),
+            Length::Sm * 2.5,
+            Length::None + Length::Px(1.0),
+            Length::max([Length::None, Length::Auto]),
+            Length::min([Length::Vw(50.0), Length::max([Length::Xl, Length::Px(300.0) * 0.5 + Length::Rem(1.0)])]),
+        ];
+        for length in lengths {
+            let json = serde_json::to_string(&length).unwrap();
+            assert_eq!(serde_json::from_str::<Length>(&json).unwrap(), length, "{}", json);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Two-way bindings between component state and the page URL, so links can
+//! point at a specific tab or sort and back/forward restore it.
+//!
+//! ```ignore
+//! html!("div", { .future(url_state::bind("tab", selected.clone())) })
+//! ```
+
+use std::{
+    future::{poll_fn, Future},
+    pin::Pin,
+    rc::Rc,
+    task::Poll,
+};
+
+use futures_signals::signal::{Mutable, Signal, SignalExt};
+use gloo::events::EventListener;
+use wasm_bindgen::JsValue;
+use web_sys::{Url, UrlSearchParams};
+
+/// Where a value lives in the URL. Hash parameters treat the fragment as a
+/// query string (`#tab=a&sort=b`), replacing plain anchors such as `#table`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UrlParam {
+    Query(String),
+    Hash(String),
+}
+
+impl From<&str> for UrlParam {
+    fn from(name: &str) -> Self {
+        UrlParam::Query(name.to_string())
+    }
+}
+
+impl From<String> for UrlParam {
+    fn from(name: String) -> Self {
+        UrlParam::Query(name)
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryMode {
+    /// Adds a history entry, so back returns to the previous value
+    #[default]
+    Push,
+    /// Rewrites the current history entry
+    Replace,
+}
+
+struct Location {
+    href: Mutable<String>,
+    _listener: Option<EventListener>,
+}
+
+thread_local! {
+    static LOCATION: Location = {
+        let href = Mutable::new(current_href());
+        // popstate covers back/forward and anchor navigation, our own writes update `href` directly
+        let listener = web_sys::window().map(|window| {
+            EventListener::new(&window, "popstate", {
+                let href = href.clone();
+                move |_| href.set_neq(current_href())
+            })
+        });
+        Location { href, _listener: listener }
+    };
+}
+
+pub(crate) fn current_href() -> String {
+    web_sys::window()
+        .and_then(|window| window.location().href().ok())
+        .unwrap_or_default()
+}
+
+fn url() -> Option<Url> {
+    Url::new(&current_href()).ok()
+}
+
+fn hash_params(url: &Url) -> Option<UrlSearchParams> {
+    UrlSearchParams::new_with_str(url.hash().trim_start_matches('#')).ok()
+}
+
+/// The page URL, updated on navigation and on writes made through this module
+pub fn location_signal() -> impl Signal<Item = String> {
+    LOCATION.with(|location| location.href.signal_cloned())
+}
+
+/// The current value of a parameter
+pub fn get(param: &UrlParam) -> Option<String> {
+    let url = url()?;
+    match param {
+        UrlParam::Query(name) => url.search_params().get(name),
+        UrlParam::Hash(name) => hash_params(&url)?.get(name),
+    }
+}
+
+/// Sets or, given `None`, removes a parameter
+pub fn set(param: &UrlParam, value: Option<&str>, mode: HistoryMode) {
+    let Some(url) = url() else {
+        return;
+    };
+    let update = |params: &UrlSearchParams, name: &str| match value {
+        Some(value) => params.set(name, value),
+        None => params.delete(name),
+    };
+    match param {
+        UrlParam::Query(name) => update(&url.search_params(), name),
+        UrlParam::Hash(name) => {
+            let Some(params) = hash_params(&url) else {
+                return;
+            };
+            update(&params, name);
+            url.set_hash(&String::from(params.to_string()));
+        }
+    }
+
+    navigate(&url.href(), mode);
+}
+
+/// Moves to another URL without reloading the page
+pub fn navigate(href: &str, mode: HistoryMode) {
+    let Some(href) = Url::new_with_base(href, &current_href()).ok().map(|url| url.href()) else {
+        return;
+    };
+    if href == current_href() {
+        return;
+    }
+    let Some(history) = web_sys::window().and_then(|window| window.history().ok()) else {
+        return;
+    };
+    let _ = match mode {
+        HistoryMode::Push => history.push_state_with_url(&JsValue::NULL, "", Some(&href)),
+        HistoryMode::Replace => history.replace_state_with_url(&JsValue::NULL, "", Some(&href)),
+    };
+    LOCATION.with(|location| location.href.set_neq(href));
+}
+
+/// Binds a string to a parameter, see [`bind_with`]
+pub fn bind(param: impl Into<UrlParam>, mutable: Mutable<String>) -> impl Future<Output = ()> {
+    bind_with(param, mutable, String::clone, |value| Some(value.to_string()))
+}
+
+/// Binds a mutable to a parameter, reading it from the URL straight away.
+/// The mutable's current value is the default, it is left out of the URL and
+/// restored when navigating to a URL without the parameter, as it is when
+/// `decode` rejects a value.
+///
+/// The returned future keeps both directions in sync, tie it to an element with `.future(..)`.
+/// Its first write, normalising a URL the mutable disagrees with, replaces the
+/// current history entry, later changes push new ones.
+pub fn bind_with<T>(
+    param: impl Into<UrlParam>,
+    mutable: Mutable<T>,
+    encode: impl Fn(&T) -> String + 'static,
+    decode: impl Fn(&str) -> Option<T> + 'static,
+) -> impl Future<Output = ()>
+where
+    T: Clone + 'static,
+{
+    let param = param.into();
+    let encode = Rc::new(encode);
+    let default = mutable.get_cloned();
+    let default_encoded = encode(&default);
+
+    let from_url = {
+        let encode = encode.clone();
+        move |mutable: &Mutable<T>, param: &UrlParam| {
+            let value = get(param).and_then(|value| decode(&value)).unwrap_or_else(|| default.clone());
+            if encode(&value) != encode(&mutable.lock_ref()) {
+                mutable.set(value);
+            }
+        }
+    };
+    from_url(&mutable, &param);
+
+    let reading = location_signal().for_each({
+        let mutable = mutable.clone();
+        let param = param.clone();
+        move |_| {
+            from_url(&mutable, &param);
+            async {}
+        }
+    });
+
+    let mut mode = HistoryMode::Replace;
+    let writing = mutable.signal_cloned().for_each(move |value| {
+        let encoded = encode(&value);
+        if encoded != get(&param).unwrap_or_else(|| default_encoded.clone()) {
+            let value = (encoded != default_encoded).then_some(encoded.as_str());
+            set(&param, value, mode);
+        }
+        mode = HistoryMode::Push;
+        async {}
+    });
+
+    let mut futures: [Option<Pin<Box<dyn Future<Output = ()>>>>; 2] =
+        [Some(Box::pin(reading)), Some(Box::pin(writing))];
+    poll_fn(move |cx| {
+        for slot in futures.iter_mut() {
+            if let Some(future) = slot {
+                if future.as_mut().poll(cx).is_ready() {
+                    *slot = None;
+                }
+            }
+        }
+        if futures.iter().all(Option::is_none) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+}
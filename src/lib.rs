@@ -1,7 +1,18 @@
+// Lets derive output name `::domcom` paths inside this crate too
+extern crate self as domcom;
+
 pub mod components;
 pub mod helpers;
 
-pub static TAILWIND: &'static [u8] = include_bytes!("../assets/tw.css"); 
+pub static TAILWIND: &[u8] = include_bytes!("../assets/tw.css");
+
+#[cfg(feature = "derive")]
+pub use domcom_derive::{DomcomForm, DomcomTable};
+
+/// Crates named by derive output, so users needn't depend on them directly
+pub mod reexports {
+    pub use dominator;
+}
 
 #[cfg(feature = "example")]
 pub mod example;